@@ -1,5 +1,7 @@
 use crate::common::*;
+use crate::description::*;
 
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
 use xml::{
@@ -7,20 +9,29 @@ use xml::{
     reader::{EventReader, XmlEvent},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Attribute {
+    #[serde(default)]
+    pub id: String,
     pub access: Access,
     pub is_static: bool,
     pub ctype: String,
     pub name: String,
-    pub brief: Option<String>,
-    pub detailed: Option<String>,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
     pub location: Option<Location>,
 }
 
+impl Default for Attribute {
+    fn default() -> Self {
+        Attribute::new()
+    }
+}
+
 impl Attribute {
     pub fn new() -> Attribute {
         Attribute {
+            id: String::new(),
             access: Access::Private,
             is_static: false,
             ctype: String::new(),
@@ -33,10 +44,12 @@ impl Attribute {
 
     pub fn read(
         parser: &mut EventReader<BufReader<File>>,
-        xml_attributes: &Vec<OwnedAttribute>,
+        xml_attributes: &[OwnedAttribute],
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<Attribute, std::io::Error> {
         let mut attr = Attribute::new();
 
+        attr.id = read_xml_attribute(xml_attributes, "id").unwrap_or_default();
         attr.is_static = read_xml_attribute(xml_attributes, "static").unwrap_or_default() == "yes";
         attr.access = match read_xml_attribute(xml_attributes, "prot") {
             Some(val) => access_from_str(val.as_str()).unwrap_or(Access::Private),
@@ -52,15 +65,13 @@ impl Attribute {
                 }) => match name.local_name.as_str() {
                     "type" => attr.ctype = read_characters_only(parser)?.unwrap_or_default(),
                     "name" => attr.name = read_characters_only(parser)?.unwrap_or_default(),
-                    "briefdescription" => attr.brief = read_description(parser)?,
-                    "detaileddescription" => attr.detailed = read_description(parser)?,
+                    "briefdescription" => attr.brief = read_description(parser, warnings)?,
+                    "detaileddescription" => attr.detailed = read_description(parser, warnings)?,
                     "location" => attr.location = Some(Location::read(attributes)),
                     _ => {}
                 },
-                Ok(XmlEvent::EndElement { ref name }) => {
-                    if name.local_name == "memberdef" {
-                        break;
-                    }
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "memberdef" => {
+                    break;
                 }
                 Err(e) => {
                     return Err(std::io::Error::new(
@@ -1,16 +1,77 @@
 use crate::attribute::*;
 use crate::class::*;
 use crate::common::*;
+use crate::description::*;
+use crate::diagnostic::*;
+use crate::enumerated::*;
+use crate::file::*;
+use crate::function::*;
+use crate::html::*;
+use crate::metrics::*;
+use crate::paths::*;
+use crate::project::*;
+use crate::property::*;
+use crate::sarif::*;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::Path;
 pub struct CsvErrorWriter {
     pub file: File,
 }
 pub trait ErrorWriter {
-    fn append(&mut self, error: String, location: &Option<Location>) -> Result<(), std::io::Error>;
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error>;
+    /// Called once all the diagnostics are appended
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
     fn name(&self) -> &'static str;
 }
 
+/// Format and destination file of a writer given as `vs`, `format:file` or
+/// a file name whose extension gives the format
+pub fn parse_writer_spec(spec: &str) -> (&'static str, Option<&str>) {
+    if spec.eq_ignore_ascii_case("vs") {
+        return ("vs", None);
+    }
+    if let Some((format, filename)) = spec.split_once(':') {
+        match format.to_lowercase().as_str() {
+            "csv" => return ("csv", Some(filename)),
+            "sarif" => return ("sarif", Some(filename)),
+            "html" => return ("html", Some(filename)),
+            _ => {}
+        }
+    }
+    let lower = spec.to_lowercase();
+    if lower.ends_with(".sarif") {
+        ("sarif", Some(spec))
+    } else if lower.ends_with(".html") || lower.ends_with(".htm") {
+        ("html", Some(spec))
+    } else {
+        ("csv", Some(spec))
+    }
+}
+
+/// Writer of `spec` writing the locations as given by `mapping`
+pub fn create_error_writer(
+    spec: &str,
+    mapping: &PathMapping,
+) -> Result<Box<dyn ErrorWriter>, std::io::Error> {
+    let writer: Box<dyn ErrorWriter> = match parse_writer_spec(spec) {
+        ("sarif", Some(filename)) => Box::new(SarifErrorWriter::new(
+            filename,
+            mapping.source_root.as_deref(),
+        )?),
+        // The page shows the source lines, it reads them before mapping
+        // the locations itself
+        ("html", Some(filename)) => return Ok(Box::new(HtmlErrorWriter::new(filename, mapping)?)),
+        (_, Some(filename)) => Box::new(CsvErrorWriter::new(filename)?),
+        (_, None) => Box::new(VisualStudioErrorWriter {}),
+    };
+    Ok(mapping.writer(writer))
+}
+
 impl CsvErrorWriter {
     pub fn new(filename: &str) -> Result<CsvErrorWriter, std::io::Error> {
         Ok(CsvErrorWriter {
@@ -19,14 +80,22 @@ impl CsvErrorWriter {
     }
 }
 impl ErrorWriter for CsvErrorWriter {
-    fn append(&mut self, error: String, location: &Option<Location>) -> Result<(), std::io::Error> {
-        self.file.write_fmt(format_args!("{};", error))?;
-        match location {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        self.file
+            .write_fmt(format_args!("{};", diagnostic.message))?;
+        match &diagnostic.location {
             Some(loc) => self
                 .file
-                .write_fmt(format_args!("{};{};\n", loc.file, loc.line))?,
-            None => self.file.write_all(b";;\n")?,
+                .write_fmt(format_args!("{};{};", loc.file, loc.line))?,
+            None => self.file.write_all(b";;")?,
         };
+        self.file.write_fmt(format_args!(
+            "{};{};{};{};\n",
+            diagnostic.rule,
+            diagnostic.severity,
+            diagnostic.author.as_deref().unwrap_or_default(),
+            diagnostic.owners.join(" ")
+        ))?;
         Ok(())
     }
 
@@ -37,17 +106,39 @@ impl ErrorWriter for CsvErrorWriter {
 
 pub struct VisualStudioErrorWriter {}
 
+/// ` [author: ..., owners: ...]` suffix of attributed diagnostics
+fn attribution_suffix(diagnostic: &Diagnostic) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(author) = &diagnostic.author {
+        parts.push(format!("author: {}", author));
+    }
+    if !diagnostic.owners.is_empty() {
+        parts.push(format!("owners: {}", diagnostic.owners.join(" ")));
+    }
+    match parts.is_empty() {
+        true => String::new(),
+        false => format!(" [{}]", parts.join(", ")),
+    }
+}
+
 impl ErrorWriter for VisualStudioErrorWriter {
-    fn append(&mut self, error: String, location: &Option<Location>) -> Result<(), std::io::Error> {
-        match location {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        match &diagnostic.location {
             Some(loc) => println!(
-                "{filename}({line}):{level}:{text}\n",
+                "{filename}({line}):{severity} {rule}:{text}{attribution}\n",
                 filename = loc.file,
                 line = loc.line,
-                level = "warning",
-                text = error
+                severity = diagnostic.severity,
+                rule = diagnostic.rule,
+                text = diagnostic.message,
+                attribution = attribution_suffix(diagnostic)
+            ),
+            None => println!(
+                ":{severity} {rule}:{text}\n",
+                severity = diagnostic.severity,
+                rule = diagnostic.rule,
+                text = diagnostic.message
             ),
-            None => println!(":{level}:{text}\n", level = "warning", text = error),
         };
 
         Ok(())
@@ -58,15 +149,250 @@ impl ErrorWriter for VisualStudioErrorWriter {
     }
 }
 
+/// Give each diagnostic the severity configured for its rule, rules turned
+/// off are dropped
+pub struct SeverityErrorWriter {
+    pub severities: HashMap<Rule, Severity>,
+    pub inner: Box<dyn ErrorWriter>,
+}
+
+impl ErrorWriter for SeverityErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        match self.severities.get(&diagnostic.rule) {
+            Some(Severity::Off) => Ok(()),
+            Some(severity) => self.inner.append(&Diagnostic {
+                severity: *severity,
+                ..diagnostic.clone()
+            }),
+            None => self.inner.append(diagnostic),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        self.inner.finish()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// Send the diagnostics to several writers
+pub struct CompositeErrorWriter {
+    pub writers: Vec<Box<dyn ErrorWriter>>,
+}
+
+impl ErrorWriter for CompositeErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        for writer in self.writers.iter_mut() {
+            writer.append(diagnostic)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        for writer in self.writers.iter_mut() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self.writers.as_slice() {
+            [writer] => writer.name(),
+            _ => "Composite",
+        }
+    }
+}
+
+/// `line` (1-based) and the lines of the declaration documented by the
+/// comment block containing it, or of the declaration starting at it
+fn documented_lines(lines: &[String], line: i32) -> Vec<i32> {
+    if line < 1 || line as usize > lines.len() {
+        return vec![line];
+    }
+    let first = line as usize - 1;
+    let before = lines[..first].join("\n");
+    let mut in_block = match (before.rfind("/*"), before.rfind("*/")) {
+        (Some(start), Some(end)) => start > end,
+        (Some(_), None) => true,
+        _ => false,
+    };
+
+    // First line of code after the comment block
+    let mut index = first;
+    while index < lines.len() {
+        let text = lines[index].trim();
+        if in_block {
+            match text.find("*/") {
+                Some(end) if !text[end + 2..].trim().is_empty() => break,
+                Some(_) => in_block = false,
+                None => {}
+            }
+        } else if let Some(comment) = text.strip_prefix("/*") {
+            match comment.find("*/") {
+                Some(end) if !comment[end + 2..].trim().is_empty() => break,
+                Some(_) => {}
+                None => in_block = true,
+            }
+        } else if !text.is_empty() && !text.starts_with("//") {
+            break;
+        }
+        index += 1;
+    }
+
+    let mut documented = vec![line];
+    for (offset, text) in lines[index..].iter().take(10).enumerate() {
+        if index + offset != first {
+            documented.push((index + offset) as i32 + 1);
+        }
+        if text.contains(';') || text.contains('{') {
+            break;
+        }
+    }
+    documented
+}
+
+/// Forward the diagnostics and remember their rule and location
+pub struct RecordingErrorWriter<'a> {
+    pub inner: &'a mut dyn ErrorWriter,
+    pub seen: HashSet<(Rule, String, i32)>,
+    sources: HashMap<String, Vec<String>>,
+}
+
+impl<'a> RecordingErrorWriter<'a> {
+    pub fn new(inner: &'a mut dyn ErrorWriter) -> RecordingErrorWriter<'a> {
+        RecordingErrorWriter {
+            inner,
+            seen: HashSet::new(),
+            sources: HashMap::new(),
+        }
+    }
+
+    /// True if a diagnostic of the same rule was written for the same
+    /// entity, doxygen reports the problems of a comment block at the line
+    /// of the comment while the rules use the line of the declaration
+    pub fn contains(&mut self, diagnostic: &Diagnostic) -> bool {
+        let loc = match &diagnostic.location {
+            Some(loc) => loc,
+            None => return false,
+        };
+        documented_lines(read_lines(&mut self.sources, &loc.file), loc.line)
+            .into_iter()
+            .any(|line| {
+                self.seen
+                    .contains(&(diagnostic.rule, loc.file.clone(), line))
+            })
+    }
+}
+
+impl<'a> ErrorWriter for RecordingErrorWriter<'a> {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        if let Some(loc) = &diagnostic.location {
+            self.seen
+                .insert((diagnostic.rule, loc.file.clone(), loc.line));
+        }
+        self.inner.append(diagnostic)
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        self.inner.finish()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// File naming expected for the header of a class
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeaderCase {
+    Exact,
+    Lower,
+    Snake,
+}
+
+impl HeaderCase {
+    pub fn from_name(name: &str) -> Option<HeaderCase> {
+        match name.to_lowercase().as_str() {
+            "exact" => Some(HeaderCase::Exact),
+            "lower" => Some(HeaderCase::Lower),
+            "snake" => Some(HeaderCase::Snake),
+            _ => None,
+        }
+    }
+
+    /// File name, without extension, of the header declaring `class_name`
+    pub fn file_stem(&self, class_name: &str) -> String {
+        match self {
+            HeaderCase::Exact => String::from(class_name),
+            HeaderCase::Lower => class_name.to_lowercase(),
+            HeaderCase::Snake => {
+                let chars: Vec<char> = class_name.chars().collect();
+                let mut stem = String::new();
+                for (i, c) in chars.iter().enumerate() {
+                    if c.is_uppercase() && i > 0 && chars[i - 1] != '_' {
+                        let previous_lower = !chars[i - 1].is_uppercase();
+                        let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                        if previous_lower || next_lower {
+                            stem.push('_');
+                        }
+                    }
+                    stem.extend(c.to_lowercase());
+                }
+                stem
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    /// Expected file name of class headers, not checked when `None`
+    pub header_case: Option<HeaderCase>,
+    /// Maximum number of public classes per header, not checked when `None`
+    pub max_classes_per_header: Option<usize>,
+    pub qt: bool,
+    pub limits: MetricLimits,
+    /// Namespaces whose public classes must belong to a group
+    pub group_namespaces: Vec<String>,
+    /// Pattern of the license header, matched against the first lines of headers
+    pub license: Option<Regex>,
+    pub macro_prefix: Option<String>,
+    /// Macros marking deprecated declarations besides `DEPRECATED`
+    pub deprecated_macros: Vec<String>,
+}
+
+/// Name without its scope, `None` for empty or anonymous (`@0`) names
+fn short_name(name: &str) -> Option<&str> {
+    let (_, short) = split_scope(name);
+    if short.is_empty() || short.starts_with('@') {
+        None
+    } else {
+        Some(short)
+    }
+}
+
+fn starts_with_uppercase(name: &str) -> bool {
+    match name.chars().next() {
+        Some(c) => c.is_uppercase(),
+        None => true,
+    }
+}
+
 fn check_attribute_name(
     error_writer: &mut dyn ErrorWriter,
     attribute: &Attribute,
     class: &Class,
 ) -> Result<(), std::io::Error> {
+    if short_name(&attribute.name).is_none() {
+        return Ok(());
+    }
     if attribute.is_static {
         if attribute.ctype.starts_with("const") {
-            if !attribute.name.chars().next().unwrap().is_uppercase() {
-                error_writer.append(
+            if !starts_with_uppercase(&attribute.name) {
+                error_writer.append(&Diagnostic::new(
+                    Rule::StaticConstAttributeName,
                     format!(
                         "Static const attribute {} of {} {} should start with an upper case letter",
                         attribute.name,
@@ -74,11 +400,12 @@ fn check_attribute_name(
                         class.name
                     ),
                     &attribute.location,
-                )?;
+                ))?;
             }
         } else {
             if !attribute.name.starts_with("s_") {
-                error_writer.append(
+                error_writer.append(&Diagnostic::new(
+                    Rule::StaticAttributeName,
                     format!(
                         "Static attribute {} of {} {} should start with a s_",
                         attribute.name,
@@ -86,18 +413,19 @@ fn check_attribute_name(
                         class.name
                     ),
                     &attribute.location,
-                )?;
+                ))?;
             }
         }
     } else {
         if !class.is_struct && !attribute.name.starts_with("m_") {
-            error_writer.append(
+            error_writer.append(&Diagnostic::new(
+                Rule::AttributeName,
                 format!(
                     "Attribute {} of class {} should start with a m_",
                     attribute.name, class.name
                 ),
                 &attribute.location,
-            )?;
+            ))?;
         }
         //  else if class.is_struct && attribute.name.starts_with("m_") {
         //     println!(
@@ -109,161 +437,1352 @@ fn check_attribute_name(
     Ok(())
 }
 
+pub fn check_enum(
+    enumerated: &Enumerated,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    if !short_name(&enumerated.name).is_none_or(starts_with_uppercase) {
+        error_writer.append(&Diagnostic::new(
+            Rule::EnumNameCase,
+            format!(
+                "Name of enum {} should start with an upper case letter",
+                enumerated.full_name
+            ),
+            &enumerated.location,
+        ))?;
+    }
+
+    if enumerated.brief.is_none() {
+        error_writer.append(&Diagnostic::new(
+            Rule::EnumDescription,
+            format!("Enum {} has no description", enumerated.full_name),
+            &enumerated.location,
+        ))?;
+    }
+
+    for value in &enumerated.values {
+        if value.brief.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::EnumValueDescription,
+                format!(
+                    "Value {} of enum {} has not decription",
+                    value.name, enumerated.full_name
+                ),
+                &enumerated.location,
+            ))?;
+        }
+        if !starts_with_uppercase(&value.name) {
+            error_writer.append(&Diagnostic::new(
+                Rule::EnumValueNameCase,
+                format!(
+                    "Value {} of enum {} should start with an upper case letter",
+                    value.name, enumerated.full_name
+                ),
+                &enumerated.location,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
 pub fn check_class(
     class: &Class,
     error_writer: &mut dyn ErrorWriter,
 ) -> Result<(), std::io::Error> {
     //println!("Checking class {}...", class.name);
 
-    assert!(!class.name.is_empty());
-
-    let pos = match class.name.rfind(':'){
-       Some(p) => std::cmp::min(p + 1, class.name.len() - 1),
-       None => 0,
-    };
-    if !class.name.chars().nth(pos).unwrap().is_uppercase() {
-        error_writer.append(
+    if !short_name(&class.name).is_none_or(starts_with_uppercase) {
+        error_writer.append(&Diagnostic::new(
+            Rule::ClassNameCase,
             format!(
                 "Name of class {} should start with an upper case letter",
                 class.name
             ),
             &class.location,
-        )?;
+        ))?;
     }
     if class.brief.is_none() && class.detailed.is_none() {
-        error_writer.append(
+        error_writer.append(&Diagnostic::new(
+            Rule::ClassDescription,
             format!("Class {} has no description", class.name),
             &class.location,
-        )?;
+        ))?;
     }
 
     for enumerated in &class.enums {
-        assert!(!enumerated.name.is_empty());
-
-        let pos = match enumerated.name.rfind(':'){
-        Some(p) => std::cmp::min(p + 1, enumerated.name.len() - 1),
-        None => 0,
-        };
-
-        if !enumerated.name.chars().nth(pos).unwrap().is_uppercase() {
-            error_writer.append(
-                format!(
-                    "Name of enum {} should start with an upper case letter",
-                    enumerated.full_name
-                ),
-                &enumerated.location,
-            )?;
-        }
-
-        if enumerated.brief.is_none() {
-            error_writer.append(
-                format!("Enum {} has no description", enumerated.full_name),
-                &enumerated.location,
-            )?;
-        }
-
-        for value in &enumerated.values {
-            if value.brief.is_none() {
-                error_writer.append(
-                    format!(
-                        "Value {} of enum {} has not decription",
-                        value.name, enumerated.full_name
-                    ),
-                    &enumerated.location,
-                )?;
-            }
-            if !value.name.chars().next().unwrap().is_uppercase() {
-                error_writer.append(
-                    format!(
-                        "Value {} of enum {} should start with an upper case letter",
-                        value.name, enumerated.full_name
-                    ),
-                    &enumerated.location,
-                )?;
-            }
-        }
+        check_enum(enumerated, error_writer)?;
     }
 
     for a in &class.attributes {
-        check_attribute_name(error_writer, &a, &class)?;
-        if a.brief == Option::None && a.detailed == Option::None {
-            error_writer.append(
+        check_attribute_name(error_writer, a, class)?;
+        if a.brief.is_none() && a.detailed.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::AttributeDescription,
                 format!(
                     "Attribute {} of class {} should have a description",
                     a.name, class.name
                 ),
                 &a.location,
-            )?;
+            ))?;
         }
         if a.access == Access::Public && !class.is_struct && !a.is_static {
             // add is_const
-            error_writer.append(
+            error_writer.append(&Diagnostic::new(
+                Rule::PublicAttribute,
                 format!(
                     "Attribute {} of class {} should not be public",
                     a.name, class.name
                 ),
                 &a.location,
-            )?;
+            ))?;
         }
     }
 
     for f in &class.functions {
-        if f.detailed == Option::None && f.brief == Option::None {
-            error_writer.append(
+        if f.detailed.is_none() && f.brief.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::FunctionDescription,
                 format!(
                     "Function {} of class {} should have a description",
                     f.name, class.name
                 ),
                 &f.location,
-            )?;
+            ))?;
         }
-        if f.ret_type.is_some() {
-            let ret_type = f.ret_type.as_ref().unwrap();
-            if !ret_type.contains("void") && ret_type != "" && f.ret_description.is_none() {
-                error_writer.append(
+        if let Some(ret_type) = &f.ret_type {
+            if !ret_type.contains("void") && !ret_type.is_empty() && f.ret_description.is_none() {
+                error_writer.append(&Diagnostic::new(
+                    Rule::ReturnDescription,
                     format!(
                     "Function {} of class {} should have a description for the returned type ({})",
                     f.name, class.name, ret_type
                 ),
                     &f.location,
-                )?;
+                ))?;
             }
         }
 
         for p in &f.parameters {
-            if p.ctype == Option::None {
-                error_writer.append(
+            let param_name = p.name.as_deref().unwrap_or_default();
+            if p.ctype.is_none() {
+                error_writer.append(&Diagnostic::new(
+                    Rule::UnknownParameter,
                     format!(
                         "Parameter {} of function {} of class {} doesn't exist",
-                        p.name.as_ref().unwrap(),
-                        f.name,
-                        class.name
+                        param_name, f.name, class.name
                     ),
                     &f.location,
-                )?;
-            } else if p.description == Option::None {
-                error_writer.append(
+                ))?;
+            } else if p.description.is_none() {
+                error_writer.append(&Diagnostic::new(
+                    Rule::ParameterDescription,
                     format!(
                         "Parameter {} of function {} of class {} should have a description",
-                        p.name.as_ref().unwrap(),
-                        f.name,
-                        class.name
+                        param_name, f.name, class.name
                     ),
                     &f.location,
-                )?;
-            } else if !p.name.as_ref().unwrap().starts_with("p_") {
-                error_writer.append(
+                ))?;
+            } else if !param_name.starts_with("p_") {
+                error_writer.append(&Diagnostic::new(
+                    Rule::ParameterName,
                     format!(
                         "Parameter {} of function {} of class {} should start with p_",
-                        p.name.as_ref().unwrap(),
-                        f.name,
-                        class.name
+                        param_name, f.name, class.name
                     ),
                     &f.location,
+                ))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the `Q_PROPERTY` declaration of `property` has a NOTIFY or a
+/// CONSTANT part, doxygen does not write them in its XML output
+fn declared_notify(property: &Property, sources: &mut HashMap<String, Vec<String>>) -> bool {
+    let location = match &property.location {
+        Some(location) if location.line > 0 => location,
+        _ => return false,
+    };
+    let declaration = read_lines(sources, &location.file)
+        .iter()
+        .skip(location.line as usize - 1)
+        .take(10)
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut declared = Property::new();
+    declared.read_macro(&declaration);
+    declared.notify.is_some() || declared.is_constant
+}
+
+/// Rules on Qt properties and signals
+pub fn check_qt_class(
+    class: &Class,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    let mut sources: HashMap<String, Vec<String>> = HashMap::new();
+    for p in &class.properties {
+        if p.brief.is_none() && p.detailed.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::PropertyDescription,
+                format!(
+                    "Property {} of class {} should have a description",
+                    p.name, class.name
+                ),
+                &p.location,
+            ))?;
+        }
+        if p.notify.is_none() && !p.is_constant && !declared_notify(p, &mut sources) {
+            error_writer.append(&Diagnostic::new(
+                Rule::PropertyNotify,
+                format!(
+                    "Property {} of class {} should name its NOTIFY signal",
+                    p.name, class.name
+                ),
+                &p.location,
+            ))?;
+        }
+    }
+
+    for f in class
+        .functions
+        .iter()
+        .filter(|f| f.kind == FunctionKind::Signal)
+    {
+        if f.ret_description.is_some() {
+            error_writer.append(&Diagnostic::new(
+                Rule::SignalReturn,
+                format!(
+                    "Signal {} of class {} should not document a returned value",
+                    f.name, class.name
+                ),
+                &f.location,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_duplicate_class_names(
+    project: &Project,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    let mut names: BTreeMap<&str, Vec<&Class>> = BTreeMap::new();
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        if project.outer_class(class).is_some() {
+            continue;
+        }
+        if let Some(name) = short_name(&class.name) {
+            names.entry(name).or_default().push(class);
+        }
+    }
+
+    for classes in names.values().filter(|classes| classes.len() > 1) {
+        for class in classes {
+            let others: Vec<&str> = classes
+                .iter()
+                .filter(|other| other.name != class.name)
+                .map(|other| other.name.as_str())
+                .collect();
+            error_writer.append(&Diagnostic::new(
+                Rule::DuplicateClassName,
+                format!(
+                    "Name of {} {} is also used by {}",
+                    class.object_type(),
+                    class.name,
+                    others.join(", ")
+                ),
+                &class.location,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_enum_value_clashes(
+    project: &Project,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    let enums = project.enums.iter().chain(
+        project
+            .classes
+            .iter()
+            .chain(project.objects.iter())
+            .flat_map(|class| class.enums.iter()),
+    );
+
+    // Values of unscoped enums are visible in the scope enclosing the enum
+    let mut scopes: BTreeMap<&str, BTreeMap<&str, &Enumerated>> = BTreeMap::new();
+    for enumerated in enums.filter(|e| !e.is_strong) {
+        let (scope, _) = split_scope(&enumerated.full_name);
+        let values = scopes.entry(scope).or_default();
+        for value in &enumerated.values {
+            match values.get(value.name.as_str()) {
+                Some(other) if other.full_name != enumerated.full_name => {
+                    error_writer.append(&Diagnostic::new(
+                        Rule::EnumValueClash,
+                        format!(
+                            "Value {} of enum {} clashes with a value of enum {}",
+                            value.name, enumerated.full_name, other.full_name
+                        ),
+                        &enumerated.location,
+                    ))?;
+                }
+                Some(_) => {}
+                None => {
+                    values.insert(value.name.as_str(), enumerated);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Documentation of an entity with the scope used to resolve its references
+struct Documented<'a> {
+    entity: String,
+    scope: &'a str,
+    location: &'a Option<Location>,
+    descriptions: Vec<&'a Description>,
+}
+
+/// Entity a doxygen id points to
+struct Target {
+    name: String,
+    access: Access,
+    deprecated: bool,
+}
+
+fn enum_documentation<'a>(
+    enumerated: &'a Enumerated,
+    scope: &'a str,
+    documented: &mut Vec<Documented<'a>>,
+) {
+    let values = enumerated.values.iter().flat_map(|v| v.brief.iter());
+    documented.push(Documented {
+        entity: format!("enum {}", enumerated.full_name),
+        scope,
+        location: &enumerated.location,
+        descriptions: enumerated
+            .brief
+            .iter()
+            .chain(enumerated.detailed.iter())
+            .chain(values)
+            .collect(),
+    });
+}
+
+fn documentation(project: &Project) -> Vec<Documented<'_>> {
+    let mut documented = Vec::new();
+    for enumerated in &project.enums {
+        enum_documentation(
+            enumerated,
+            split_scope(&enumerated.full_name).0,
+            &mut documented,
+        );
+    }
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        let scope = class.name.as_str();
+        documented.push(Documented {
+            entity: format!("{} {}", class.object_type(), class.name),
+            scope,
+            location: &class.location,
+            descriptions: class.brief.iter().chain(class.detailed.iter()).collect(),
+        });
+        for a in &class.attributes {
+            documented.push(Documented {
+                entity: format!("attribute {} of class {}", a.name, class.name),
+                scope,
+                location: &a.location,
+                descriptions: a.brief.iter().chain(a.detailed.iter()).collect(),
+            });
+        }
+        for p in &class.properties {
+            documented.push(Documented {
+                entity: format!("property {} of class {}", p.name, class.name),
+                scope,
+                location: &p.location,
+                descriptions: p.brief.iter().chain(p.detailed.iter()).collect(),
+            });
+        }
+        for f in &class.functions {
+            let parameters = f.parameters.iter().flat_map(|p| p.description.iter());
+            documented.push(Documented {
+                entity: format!("function {} of class {}", f.name, class.name),
+                scope,
+                location: &f.location,
+                descriptions: f
+                    .brief
+                    .iter()
+                    .chain(f.detailed.iter())
+                    .chain(f.ret_description.iter())
+                    .chain(parameters)
+                    .collect(),
+            });
+        }
+        for enumerated in &class.enums {
+            enum_documentation(enumerated, scope, &mut documented);
+        }
+    }
+    documented
+}
+
+fn enum_targets<'a>(
+    enumerated: &'a Enumerated,
+    targets: &mut HashMap<&'a str, Target>,
+    names: &mut HashSet<String>,
+) {
+    let deprecated = is_deprecated(&enumerated.brief, &enumerated.detailed);
+    targets.insert(
+        &enumerated.id,
+        Target {
+            name: enumerated.full_name.clone(),
+            access: Access::Public,
+            deprecated,
+        },
+    );
+    names.insert(enumerated.full_name.clone());
+    let (scope, _) = split_scope(&enumerated.full_name);
+    for value in &enumerated.values {
+        let name = format!("{}::{}", enumerated.full_name, value.name);
+        targets.insert(
+            &value.id,
+            Target {
+                name: name.clone(),
+                access: Access::Public,
+                deprecated,
+            },
+        );
+        names.insert(name);
+        if !enumerated.is_strong {
+            names.insert(match scope.is_empty() {
+                true => value.name.clone(),
+                false => format!("{}::{}", scope, value.name),
+            });
+        }
+    }
+}
+
+/// Index the project entities by doxygen id and by qualified name
+fn targets(project: &Project) -> (HashMap<&str, Target>, HashSet<String>) {
+    let mut targets = HashMap::new();
+    let mut names = HashSet::new();
+    for enumerated in &project.enums {
+        enum_targets(enumerated, &mut targets, &mut names);
+    }
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        targets.insert(
+            class.id.as_str(),
+            Target {
+                name: class.name.clone(),
+                access: Access::Public,
+                deprecated: is_deprecated(&class.brief, &class.detailed),
+            },
+        );
+        names.insert(class.name.clone());
+        for a in &class.attributes {
+            let name = format!("{}::{}", class.name, a.name);
+            targets.insert(
+                a.id.as_str(),
+                Target {
+                    name: name.clone(),
+                    access: a.access,
+                    deprecated: is_deprecated(&a.brief, &a.detailed),
+                },
+            );
+            names.insert(name);
+        }
+        for p in &class.properties {
+            let name = format!("{}::{}", class.name, p.name);
+            targets.insert(
+                p.id.as_str(),
+                Target {
+                    name: name.clone(),
+                    access: Access::Public,
+                    deprecated: is_deprecated(&p.brief, &p.detailed),
+                },
+            );
+            names.insert(name);
+        }
+        for f in &class.functions {
+            let name = format!("{}::{}", class.name, f.name);
+            targets.insert(
+                f.id.as_str(),
+                Target {
+                    name: name.clone(),
+                    access: f.access,
+                    deprecated: is_deprecated(&f.brief, &f.detailed),
+                },
+            );
+            names.insert(name);
+        }
+        for enumerated in &class.enums {
+            enum_targets(enumerated, &mut targets, &mut names);
+        }
+    }
+    // Namespaces, groups, files and macros can be referenced too
+    let scopes: Vec<String> = names
+        .iter()
+        .map(|name| String::from(split_scope(name).0))
+        .collect();
+    for mut scope in scopes {
+        while !scope.is_empty() && names.insert(scope.clone()) {
+            scope = String::from(split_scope(&scope).0);
+        }
+    }
+    for group in &project.groups {
+        names.insert(group.name.clone());
+    }
+    for file in &project.files {
+        names.insert(file.name.clone());
+        names.extend(file.macros.iter().map(|m| m.name.clone()));
+    }
+    targets.remove("");
+    (targets, names)
+}
+
+/// Look a name up from the given scope and its enclosing scopes
+fn resolve(names: &HashSet<String>, scope: &str, target: &str) -> bool {
+    let target = target.replace('#', "::");
+    let target = target.split('(').next().unwrap_or_default();
+    let target = target.trim_start_matches("::");
+    let mut scope = scope;
+    loop {
+        let name = match scope.is_empty() {
+            true => String::from(target),
+            false => format!("{}::{}", scope, target),
+        };
+        if names.contains(&name) {
+            return true;
+        }
+        if scope.is_empty() {
+            return false;
+        }
+        scope = split_scope(scope).0;
+    }
+}
+
+/// `\ref` and `\copydoc` commands with their target and link text
+fn reference_commands() -> Regex {
+    Regex::new(r#"(?:^|\s)[\\@](ref|copydoc|copybrief|copydetails)\s+(\S+)(?:\s+"([^"]*)")?"#)
+        .unwrap()
+}
+
+/// Target of a `\ref` or `\copydoc` written in a comment
+struct CommentedTarget {
+    command: &'static str,
+    target: String,
+    /// Text of the link, as in `\ref target "text"`
+    text: Option<String>,
+}
+
+/// Whether a line belongs to the declaration written below it, as
+/// `template <>` or a return type alone on its line
+fn is_declaration_start(text: &str) -> bool {
+    let text = text.trim();
+    !text.is_empty()
+        && !text.starts_with('#')
+        && !is_comment_line(text)
+        && !text.ends_with([';', '{', '}', ':', ','])
+}
+
+/// `\ref` and `\copydoc` targets naming an entity, written in the comment
+/// block of a declaration
+fn commented_targets(
+    lines: &[String],
+    location: &Location,
+    command_regex: &Regex,
+) -> Vec<CommentedTarget> {
+    let mut comment: Vec<&str> = Vec::new();
+    let line = location.line as usize;
+    if line == 0 || line > lines.len() {
+        return Vec::new();
+    }
+    if let Some(index) = lines[line - 1]
+        .find("//!<")
+        .or_else(|| lines[line - 1].find("///<"))
+    {
+        comment.push(&lines[line - 1][index..]);
+    }
+    let mut above = lines[..line - 1].iter().rev().peekable();
+    // A few lines of template parameters or attributes above the name
+    for _ in 0..4 {
+        match above.peek() {
+            Some(text) if is_declaration_start(text) => {
+                above.next();
+            }
+            _ => break,
+        }
+    }
+    for text in above {
+        if is_comment_line(text) {
+            comment.push(text.trim());
+        } else {
+            break;
+        }
+    }
+
+    let mut targets = Vec::new();
+    for text in comment {
+        for captures in command_regex.captures_iter(text) {
+            let command = match &captures[1] {
+                "ref" => "\\ref",
+                _ => "\\copydoc",
+            };
+            let target = captures[2].trim_end_matches(['.', ',', ';', ':']);
+            if !is_identifier_target(target) {
+                continue;
+            }
+            targets.push(CommentedTarget {
+                command,
+                target: String::from(target),
+                text: captures.get(3).map(|text| String::from(text.as_str())),
+            });
+        }
+    }
+    targets
+}
+
+fn read_lines<'a>(sources: &'a mut HashMap<String, Vec<String>>, file: &str) -> &'a [String] {
+    sources.entry(String::from(file)).or_insert_with(|| {
+        std::fs::read_to_string(file)
+            .map(|content| content.lines().map(String::from).collect())
+            .unwrap_or_default()
+    })
+}
+
+fn check_references(
+    project: &Project,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    let (targets, names) = targets(project);
+    let mut sources: HashMap<String, Vec<String>> = HashMap::new();
+    let command_regex = reference_commands();
+
+    for documented in documentation(project) {
+        let references: Vec<&Reference> = documented
+            .descriptions
+            .iter()
+            .flat_map(|d| d.references.iter())
+            .collect();
+        for reference in &references {
+            let target = match &reference.refid {
+                Some(refid) => targets.get(refid.as_str()),
+                None => {
+                    error_writer.append(&Diagnostic::new(
+                        Rule::UnresolvedReference,
+                        format!(
+                            "Reference to {} in {} could not be resolved",
+                            reference.text, documented.entity
+                        ),
+                        documented.location,
+                    ))?;
+                    continue;
+                }
+            };
+            if let (ReferenceKind::See, Some(target)) = (reference.kind, target) {
+                if target.access == Access::Private {
+                    error_writer.append(&Diagnostic::new(
+                        Rule::SeePrivate,
+                        format!(
+                            "See also section of {} points to private {}",
+                            documented.entity, target.name
+                        ),
+                        documented.location,
+                    ))?;
+                }
+                if target.deprecated {
+                    error_writer.append(&Diagnostic::new(
+                        Rule::SeeDeprecated,
+                        format!(
+                            "See also section of {} points to deprecated {}",
+                            documented.entity, target.name
+                        ),
+                        documented.location,
+                    ))?;
+                }
+            }
+        }
+
+        // Doxygen writes unresolved \ref as plain text and drops unresolved
+        // \copydoc, so these are looked for in the sources
+        let location = match documented.location {
+            Some(location) => location,
+            None => continue,
+        };
+        let lines = read_lines(&mut sources, &location.file);
+        for commented in commented_targets(lines, location, &command_regex) {
+            // Doxygen links free functions and pages, which are not in the
+            // model, with the target or the given text
+            let linked = |text: &str| {
+                let text = text.trim().trim_end_matches("()");
+                references
+                    .iter()
+                    .any(|r| r.refid.is_some() && r.text.trim().trim_end_matches("()") == text)
+            };
+            let resolved = resolve(&names, documented.scope, &commented.target)
+                || match commented.command {
+                    "\\ref" => {
+                        linked(&commented.target) || commented.text.as_deref().is_some_and(linked)
+                    }
+                    // Doxygen copies nothing when the target is unknown
+                    _ => !documented.descriptions.is_empty(),
+                };
+            if !resolved {
+                error_writer.append(&Diagnostic::new(
+                    Rule::UnresolvedReference,
+                    format!(
+                        "Target {} of {} in {} could not be resolved",
+                        commented.target, commented.command, documented.entity
+                    ),
+                    documented.location,
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const HEADER_EXTENSIONS: [&str; 5] = ["h", "hh", "hpp", "hxx", "h++"];
+const INTERNAL_FOLDERS: [&str; 2] = ["detail", "internal"];
+
+fn is_header(file: &str) -> bool {
+    match Path::new(file).extension() {
+        Some(ext) => HEADER_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+/// True if a scope of `name` is a detail or internal namespace
+fn is_internal_name(name: &str) -> bool {
+    let mut scope = split_scope(name).0;
+    while !scope.is_empty() {
+        let (outer, last) = split_scope(scope);
+        if INTERNAL_FOLDERS.contains(&last) {
+            return true;
+        }
+        scope = outer;
+    }
+    false
+}
+
+fn is_internal_path(file: &str) -> bool {
+    // Locations written on Windows keep their separators on other systems
+    file.split(['/', '\\'])
+        .any(|folder| INTERNAL_FOLDERS.contains(&folder))
+}
+
+fn check_internal_header(
+    entity: &str,
+    name: &str,
+    location: &Option<Location>,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    if let Some(location) = location {
+        if !is_internal_name(name) && is_internal_path(&location.file) {
+            error_writer.append(&Diagnostic::new(
+                Rule::InternalHeader,
+                format!(
+                    "Public {} {} is declared in internal header {}",
+                    entity, name, location.file
+                ),
+                &Some(location.clone()),
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Pattern of the deprecation attributes and of the `DEPRECATED` macro or
+/// of the configured deprecation macros
+fn deprecation_pattern(macros: &[String]) -> Regex {
+    let mut names = vec![String::from("DEPRECATED")];
+    names.extend(macros.iter().map(|name| regex::escape(name)));
+    Regex::new(&format!(
+        r"\[\[deprecated|__declspec\(deprecated|__attribute__\(\(deprecated|\b(?:{})\b",
+        names.join("|")
+    ))
+    .unwrap()
+}
+
+/// True if the declaration line at `location` uses a deprecation attribute or macro
+fn declared_deprecated(lines: &[String], location: &Location, pattern: &Regex) -> bool {
+    let line = location.line as usize;
+    if line == 0 || line > lines.len() {
+        return false;
+    }
+    let code = lines[line - 1].split("//").next().unwrap_or_default();
+    pattern.is_match(code)
+}
+
+fn is_comment_line(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with("//") || text.starts_with("/*") || text.starts_with('*')
+}
+
+fn check_deprecated(
+    project: &Project,
+    options: &CheckOptions,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    let mut sources: HashMap<String, Vec<String>> = HashMap::new();
+    let pattern = deprecation_pattern(&options.deprecated_macros);
+    for documented in documentation(project) {
+        match documented
+            .descriptions
+            .iter()
+            .find_map(|d| d.deprecated.as_ref())
+        {
+            Some(text) if text.is_empty() => {
+                error_writer.append(&Diagnostic::new(
+                    Rule::DeprecatedDescription,
+                    format!(
+                        "Deprecated section of {} should name its replacement",
+                        documented.entity
+                    ),
+                    documented.location,
+                ))?;
+            }
+            Some(_) => {}
+            None => {
+                if let Some(location) = documented.location {
+                    if declared_deprecated(
+                        read_lines(&mut sources, &location.file),
+                        location,
+                        &pattern,
+                    ) {
+                        error_writer.append(&Diagnostic::new(
+                            Rule::DeprecatedDescription,
+                            format!(
+                                "Declaration of {} is deprecated but its documentation has no deprecated section",
+                                documented.entity
+                            ),
+                            documented.location,
+                        ))?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rules on the headers declaring the classes of the project
+fn check_locations(
+    project: &Project,
+    options: &CheckOptions,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    let mut headers: BTreeMap<&str, Vec<&Class>> = BTreeMap::new();
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        if project.outer_class(class).is_some() {
+            continue;
+        }
+        let location = match &class.location {
+            Some(location) if is_header(&location.file) => location,
+            _ => continue,
+        };
+        check_internal_header(
+            class.object_type(),
+            &class.name,
+            &class.location,
+            error_writer,
+        )?;
+        if class.is_struct || is_internal_name(&class.name) {
+            continue;
+        }
+        let short = match short_name(&class.name) {
+            Some(short) => short.split('<').next().unwrap_or_default().trim(),
+            None => continue,
+        };
+        headers
+            .entry(location.file.as_str())
+            .or_default()
+            .push(class);
+
+        let header_case = match options.header_case {
+            Some(header_case) => header_case,
+            None => continue,
+        };
+        let expected = header_case.file_stem(short);
+        let stem = Path::new(&location.file.replace('\\', "/"))
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if stem != expected {
+            error_writer.append(&Diagnostic::new(
+                Rule::HeaderName,
+                format!(
+                    "Class {} should be declared in a header named {}, not {}",
+                    class.name, expected, stem
+                ),
+                &class.location,
+            ))?;
+        }
+    }
+
+    if let Some(max_classes) = options.max_classes_per_header {
+        for (file, classes) in headers {
+            if classes.len() > max_classes {
+                let names: Vec<&str> = classes.iter().map(|c| c.name.as_str()).collect();
+                error_writer.append(&Diagnostic::new(
+                    Rule::ClassesPerHeader,
+                    format!(
+                        "Header {} declares {} public classes ({}), at most {} expected",
+                        file,
+                        classes.len(),
+                        names.join(", "),
+                        max_classes
+                    ),
+                    &classes[max_classes].location,
+                ))?;
+            }
+        }
+    }
+
+    for enumerated in &project.enums {
+        if let Some(location) = &enumerated.location {
+            if is_header(&location.file) {
+                check_internal_header(
+                    "enum",
+                    &enumerated.full_name,
+                    &enumerated.location,
+                    error_writer,
                 )?;
             }
         }
     }
+    Ok(())
+}
+
+/// Report the metrics above their limit, private functions are ignored
+fn check_metrics(
+    project: &Project,
+    limits: &MetricLimits,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    for class in project_metrics(project) {
+        if let Some(limit) = limits
+            .public_methods
+            .filter(|limit| class.public_methods > *limit)
+        {
+            error_writer.append(&Diagnostic::new(
+                Rule::PublicMethodCount,
+                format!(
+                    "Class {} has {} public methods, at most {} expected",
+                    class.name, class.public_methods, limit
+                ),
+                &class.location,
+            ))?;
+        }
+        if let Some(limit) = limits
+            .nesting_depth
+            .filter(|limit| class.nesting_depth > *limit)
+        {
+            error_writer.append(&Diagnostic::new(
+                Rule::NestingDepth,
+                format!(
+                    "Class {} is nested in {} classes, at most {} expected",
+                    class.name, class.nesting_depth, limit
+                ),
+                &class.location,
+            ))?;
+        }
+
+        let mut overloaded: HashSet<&str> = HashSet::new();
+        for f in class
+            .functions
+            .iter()
+            .filter(|f| f.access != Access::Private)
+        {
+            if let Some(limit) = limits.parameters.filter(|limit| f.parameters > *limit) {
+                error_writer.append(&Diagnostic::new(
+                    Rule::ParameterCount,
+                    format!(
+                        "Function {} of class {} has {} parameters, at most {} expected",
+                        f.name, class.name, f.parameters, limit
+                    ),
+                    &f.location,
+                ))?;
+            }
+            if let Some(limit) = limits
+                .bool_parameters
+                .filter(|limit| f.bool_parameters > *limit)
+            {
+                error_writer.append(&Diagnostic::new(
+                    Rule::BoolParameterCount,
+                    format!(
+                        "Function {} of class {} has {} boolean parameters, at most {} expected",
+                        f.name, class.name, f.bool_parameters, limit
+                    ),
+                    &f.location,
+                ))?;
+            }
+            if let Some(limit) = limits.overloads.filter(|limit| f.overloads > *limit) {
+                if !overloaded.insert(f.name.as_str()) {
+                    continue;
+                }
+                error_writer.append(&Diagnostic::new(
+                    Rule::OverloadCount,
+                    format!(
+                        "Function {} of class {} has {} overloads, at most {} expected",
+                        f.name, class.name, f.overloads, limit
+                    ),
+                    &f.location,
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
 
+/// Rules on the doxygen groups and the membership of the classes
+fn check_groups(
+    project: &Project,
+    options: &CheckOptions,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    for group in &project.groups {
+        if group.title.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::GroupTitle,
+                format!("Group {} should have a title", group.name),
+                &group.location,
+            ))?;
+        }
+        if group.brief.is_none() && group.detailed.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::GroupDescription,
+                format!("Group {} should have a description", group.display_name()),
+                &group.location,
+            ))?;
+        }
+    }
+
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        if project.outer_class(class).is_some() || is_internal_name(&class.name) {
+            continue;
+        }
+        if !options
+            .group_namespaces
+            .iter()
+            .any(|ns| class.name.starts_with(&format!("{}::", ns)))
+        {
+            continue;
+        }
+        let groups = project.groups_of(&class.id);
+        match groups.len() {
+            1 => {}
+            0 => error_writer.append(&Diagnostic::new(
+                Rule::GroupMembership,
+                format!(
+                    "{} {} should belong to a group",
+                    class.object_type(),
+                    class.name
+                ),
+                &class.location,
+            ))?,
+            _ => {
+                let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+                error_writer.append(&Diagnostic::new(
+                    Rule::GroupMembership,
+                    format!(
+                        "{} {} should belong to a single group, not {}",
+                        class.object_type(),
+                        class.name,
+                        names.join(", ")
+                    ),
+                    &class.location,
+                ))?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Number of lines of a header searched for the license
+const LICENSE_LINES: usize = 30;
+
+fn is_upper_snake_case(name: &str) -> bool {
+    !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn check_macro(
+    define: &Macro,
+    options: &CheckOptions,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    if !is_upper_snake_case(&define.name) {
+        error_writer.append(&Diagnostic::new(
+            Rule::MacroNameCase,
+            format!("Macro {} should be in upper snake case", define.name),
+            &define.location,
+        ))?;
+    }
+    if define.brief.is_none() && define.detailed.is_none() {
+        error_writer.append(&Diagnostic::new(
+            Rule::MacroDescription,
+            format!("Macro {} should have a description", define.name),
+            &define.location,
+        ))?;
+    }
+    if let Some(prefix) = &options.macro_prefix {
+        if !define.name.starts_with(prefix.as_str()) {
+            error_writer.append(&Diagnostic::new(
+                Rule::MacroPrefix,
+                format!("Macro {} should start with {}", define.name, prefix),
+                &define.location,
+            ))?;
+        }
+    }
     Ok(())
 }
+
+fn check_files(
+    project: &Project,
+    options: &CheckOptions,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    for file in &project.files {
+        let path = match &file.location {
+            Some(location) if is_header(&location.file) => location.file.as_str(),
+            _ => continue,
+        };
+        if file.brief.is_none() {
+            error_writer.append(&Diagnostic::new(
+                Rule::FileDescription,
+                format!(
+                    "Header {} should have a @file block with a brief description",
+                    file.name
+                ),
+                &file.location,
+            ))?;
+        }
+        if let Some(license) = &options.license {
+            let head: Vec<String> = match std::fs::read(path) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .take(LICENSE_LINES)
+                    .map(String::from)
+                    .collect(),
+                Err(_) => Vec::new(),
+            };
+            if !license.is_match(&head.join("\n")) {
+                error_writer.append(&Diagnostic::new(
+                    Rule::FileLicense,
+                    format!("Header {} should start with the license header", file.name),
+                    &file.location,
+                ))?;
+            }
+        }
+        for define in &file.macros {
+            check_macro(define, options, error_writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rules involving several entities of the project
+pub fn check_project(
+    project: &Project,
+    options: &CheckOptions,
+    error_writer: &mut dyn ErrorWriter,
+) -> Result<(), std::io::Error> {
+    for enumerated in &project.enums {
+        check_enum(enumerated, error_writer)?;
+    }
+    check_duplicate_class_names(project, error_writer)?;
+    check_enum_value_clashes(project, error_writer)?;
+    check_references(project, error_writer)?;
+    check_deprecated(project, options, error_writer)?;
+    check_locations(project, options, error_writer)?;
+    check_metrics(project, &options.limits, error_writer)?;
+    check_groups(project, options, error_writer)?;
+    check_files(project, options, error_writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets_of(source: &str, line: i32) -> Vec<(String, Option<String>)> {
+        let lines: Vec<String> = source.lines().map(String::from).collect();
+        let location = Location {
+            file: String::new(),
+            line,
+        };
+        let regex = reference_commands();
+        commented_targets(&lines, &location, &regex)
+            .into_iter()
+            .map(|target| (target.target, target.text))
+            .collect()
+    }
+
+    #[test]
+    fn commented_targets_above_template() {
+        let source = "/// @brief See @ref Widget.\ntemplate <>\nint get<int>();";
+        assert_eq!(targets_of(source, 3), [(String::from("Widget"), None)]);
+    }
+
+    #[test]
+    fn commented_target_with_link_text() {
+        let source = "/// See @ref sdk::Widget \"the widget\" and @ref https://x.org\nvoid f();";
+        assert_eq!(
+            targets_of(source, 2),
+            [(
+                String::from("sdk::Widget"),
+                Some(String::from("the widget"))
+            )]
+        );
+    }
+
+    #[test]
+    fn commented_targets_of_trailing_comment() {
+        let source = "int m_a; ///< @copydoc m_b\nint m_b;";
+        assert_eq!(targets_of(source, 1), [(String::from("m_b"), None)]);
+    }
+
+    #[test]
+    fn internal_paths_with_any_separator() {
+        assert!(is_internal_path("include/sdk/detail/Widget.h"));
+        assert!(is_internal_path("C:\\sdk\\internal\\Widget.h"));
+        assert!(!is_internal_path("C:\\sdk\\details\\Widget.h"));
+    }
+
+    /// Diagnostics appended by the checks
+    #[derive(Default)]
+    struct Collector {
+        diagnostics: Vec<Diagnostic>,
+    }
+
+    impl ErrorWriter for Collector {
+        fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+            self.diagnostics.push(diagnostic.clone());
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "Collector"
+        }
+    }
+
+    impl Collector {
+        fn rules(&self) -> Vec<Rule> {
+            self.diagnostics.iter().map(|d| d.rule).collect()
+        }
+    }
+
+    fn class_at(name: &str, file: &str, line: i32) -> Class {
+        let mut class = Class::new();
+        class.name = String::from(name);
+        class.location = Some(Location {
+            file: String::from(file),
+            line,
+        });
+        class
+    }
+
+    #[test]
+    fn header_naming_rules_are_opt_in() {
+        let mut project = Project::new();
+        project
+            .classes
+            .push_back(class_at("sdk::TextBox", "include/widgets.h", 3));
+        project
+            .classes
+            .push_back(class_at("sdk::Button", "include/widgets.h", 9));
+
+        let mut collector = Collector::default();
+        check_locations(&project, &CheckOptions::default(), &mut collector).unwrap();
+        assert_eq!(collector.rules(), []);
+
+        let options = CheckOptions {
+            header_case: Some(HeaderCase::Snake),
+            max_classes_per_header: Some(1),
+            ..Default::default()
+        };
+        let mut collector = Collector::default();
+        check_locations(&project, &options, &mut collector).unwrap();
+        assert_eq!(
+            collector.rules(),
+            [Rule::HeaderName, Rule::HeaderName, Rule::ClassesPerHeader]
+        );
+        let lines: Vec<i32> = collector
+            .diagnostics
+            .iter()
+            .filter_map(|d| d.location.as_ref().map(|l| l.line))
+            .collect();
+        assert_eq!(lines, [3, 9, 9]);
+    }
+
+    #[test]
+    fn metric_outliers_need_a_limit() {
+        let mut function = Function::new();
+        function.name = String::from("resize");
+        function.access = Access::Public;
+        for name in ["width", "height", "fit"] {
+            let mut parameter = Parameter::new();
+            parameter.name = Some(String::from(name));
+            parameter.ctype = Some(String::from(match name {
+                "fit" => "bool",
+                _ => "int",
+            }));
+            function.parameters.push(parameter);
+        }
+        let mut class = class_at("Widget", "widget.h", 1);
+        class.functions = vec![function];
+        let mut project = Project::new();
+        project.classes.push_back(class);
+
+        let mut collector = Collector::default();
+        check_metrics(&project, &MetricLimits::default(), &mut collector).unwrap();
+        assert_eq!(collector.rules(), []);
+
+        let mut limits = MetricLimits::default();
+        limits.set("parameters=2").unwrap();
+        limits.set("bool-parameters=1").unwrap();
+        assert!(limits.set("lines=4").is_err());
+        let mut collector = Collector::default();
+        check_metrics(&project, &limits, &mut collector).unwrap();
+        assert_eq!(collector.rules(), [Rule::ParameterCount]);
+    }
+
+    #[test]
+    fn deprecation_macros_are_whole_words() {
+        let location = Location {
+            file: String::from("a.h"),
+            line: 2,
+        };
+        let deprecated = |line: &str, macros: &[&str]| {
+            let macros: Vec<String> = macros.iter().map(|m| String::from(*m)).collect();
+            let lines = vec![String::from("SDK_DEPRECATED"), String::from(line)];
+            declared_deprecated(&lines, &location, &deprecation_pattern(&macros))
+        };
+        assert!(deprecated("DEPRECATED void f();", &[]));
+        assert!(deprecated("[[deprecated(\"use g\")]] void f();", &[]));
+        assert!(!deprecated("void f(); // DEPRECATED", &[]));
+        assert!(!deprecated("int NOT_DEPRECATED_YET = 0;", &[]));
+        assert!(!deprecated("void f();", &[]));
+        assert!(deprecated("SDK_DEPRECATED void f();", &["SDK_DEPRECATED"]));
+    }
+
+    #[test]
+    fn documented_lines_of_comment_blocks() {
+        let source = "/**\n * @brief Resize\n * @param w width\n */\n\nvoid resize(int w,\n            int h);\nint a;";
+        let lines: Vec<String> = source.lines().map(String::from).collect();
+        assert_eq!(documented_lines(&lines, 3), [3, 6, 7]);
+        assert_eq!(documented_lines(&lines, 1), [1, 6, 7]);
+        assert_eq!(documented_lines(&lines, 8), [8]);
+
+        let lines = vec![String::from("/** Size */ int size();")];
+        assert_eq!(documented_lines(&lines, 1), [1]);
+        let lines = vec![String::from("/// Size"), String::from("int size();")];
+        assert_eq!(documented_lines(&lines, 1), [1, 2]);
+    }
+}
@@ -1,38 +1,56 @@
 use crate::attribute::*;
 use crate::common::*;
+use crate::description::*;
 use crate::enumerated::*;
 use crate::function::*;
+use crate::property::*;
 
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
 use std::str;
 use xml::attribute::OwnedAttribute;
 use xml::reader::{EventReader, XmlEvent};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Class {
+    #[serde(default)]
+    pub id: String,
     pub name: String,
-    pub brief: Option<String>,
-    pub detailed: Option<String>,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
     pub attributes: Vec<Attribute>,
     pub functions: Vec<Function>,
+    #[serde(default)]
+    pub properties: Vec<Property>,
     pub enums: Vec<Enumerated>,
     pub location: Option<Location>,
     pub is_struct: bool,
+    #[serde(default)]
+    pub inner_classes: Vec<String>,
+}
+
+impl Default for Class {
+    fn default() -> Self {
+        Class::new()
+    }
 }
 
 impl Class {
-    fn new() -> Class {
-        return Class {
+    pub fn new() -> Class {
+        Class {
+            id: String::new(),
             name: String::new(),
             brief: Option::None,
             detailed: Option::None,
             attributes: Vec::new(),
             functions: Vec::new(),
+            properties: Vec::new(),
             enums: Vec::new(),
             location: Option::None,
             is_struct: false,
-        };
+            inner_classes: Vec::new(),
+        }
     }
 
     pub fn object_type(&self) -> &'static str {
@@ -50,19 +68,14 @@ impl Class {
         Ok(())
     }
 
-    fn get_kind_attr(attributes: &Vec<OwnedAttribute>) -> Result<String, std::io::Error> {
-        match attributes.iter().find(|&r| r.name.local_name == "kind") {
-            Some(val) => Ok(val.value.to_string().clone()),
-            None => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "No kind attribute found",
-            )),
-        }
+    fn get_kind_attr(attributes: &[OwnedAttribute]) -> Option<String> {
+        read_xml_attribute(attributes, "kind")
     }
 
     pub fn read_attributes(
         &mut self,
         parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<(), std::io::Error> {
         loop {
             match parser.next() {
@@ -70,15 +83,16 @@ impl Class {
                     ref name,
                     ref attributes,
                     ..
-                }) => {
-                    if name.local_name == "memberdef" {
-                        self.attributes.push(Attribute::read(parser, attributes)?);
+                }) if name.local_name == "memberdef" => {
+                    let first = warnings.len();
+                    let attribute = Attribute::read(parser, attributes, warnings)?;
+                    let entity = format!("attribute {}", attribute.name);
+                    if !skip_on_warnings(warnings, first, &entity, &attribute.location) {
+                        self.attributes.push(attribute);
                     }
                 }
-                Ok(XmlEvent::EndElement { ref name }) => {
-                    if name.local_name == "sectiondef" {
-                        break;
-                    }
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "sectiondef" => {
+                    break;
                 }
                 Err(e) => {
                     return Err(std::io::Error::new(
@@ -95,6 +109,8 @@ impl Class {
     pub fn read_functions(
         &mut self,
         parser: &mut EventReader<BufReader<File>>,
+        kind: FunctionKind,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<(), std::io::Error> {
         loop {
             match parser.next() {
@@ -102,16 +118,52 @@ impl Class {
                     ref name,
                     ref attributes,
                     ..
-                }) => {
-                    if name.local_name == "memberdef" {
-                        self.functions.push(Function::read(parser, attributes)?);
+                }) if name.local_name == "memberdef" => {
+                    let first = warnings.len();
+                    let mut function = Function::read(parser, attributes, warnings)?;
+                    function.kind = kind;
+                    let entity = format!("function {}", function.name);
+                    if !skip_on_warnings(warnings, first, &entity, &function.location) {
+                        self.functions.push(function);
                     }
                 }
-                Ok(XmlEvent::EndElement { ref name }) => {
-                    if name.local_name == "sectiondef" {
-                        break;
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "sectiondef" => {
+                    break;
+                }
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_properties(
+        &mut self,
+        parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<(), std::io::Error> {
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) if name.local_name == "memberdef" => {
+                    let first = warnings.len();
+                    let property = Property::read(parser, attributes, warnings)?;
+                    let entity = format!("property {}", property.name);
+                    if !skip_on_warnings(warnings, first, &entity, &property.location) {
+                        self.properties.push(property);
                     }
                 }
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "sectiondef" => {
+                    break;
+                }
                 Err(e) => {
                     return Err(std::io::Error::new(
                         std::io::ErrorKind::InvalidData,
@@ -127,6 +179,7 @@ impl Class {
     pub fn read_types(
         &mut self,
         parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<(), std::io::Error> {
         let mut depth: i32 = 0;
         loop {
@@ -140,15 +193,23 @@ impl Class {
                         depth += 1;
                     } else {
                         if name.local_name == "memberdef" {
-                            let kind = Class::get_kind_attr(attributes)?;
+                            let kind = Class::get_kind_attr(attributes).unwrap_or_default();
                             if kind == "enum" {
-                                let enumerated = Enumerated::read(parser)?;
-                                self.enums.push(enumerated);
+                                let first = warnings.len();
+                                let enumerated = Enumerated::read(parser, attributes, warnings)?;
+                                let entity = format!("enum {}", enumerated.full_name);
+                                if !skip_on_warnings(warnings, first, &entity, &enumerated.location)
+                                {
+                                    self.enums.push(enumerated);
+                                }
                             } else if kind == "typedef" {
                                 depth += 1;
                             } else {
                                 depth += 1;
-                                println!("Unknown class type: {}", kind);
+                                warnings.push(ParseWarning::new(format!(
+                                    "Unknown class type: {}",
+                                    kind
+                                )));
                             }
                         }
                     }
@@ -156,7 +217,8 @@ impl Class {
                 Ok(XmlEvent::EndElement { ref name }) => {
                     if depth == 0 {
                         if name.local_name != "sectiondef" {
-                            println!("Invalid end section def");
+                            warnings
+                                .push(ParseWarning::new(String::from("Invalid end section def")));
                         }
                         break;
                     }
@@ -177,6 +239,7 @@ impl Class {
     fn read_content(
         &mut self,
         parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<(), std::io::Error> {
         loop {
             match parser.next() {
@@ -186,35 +249,42 @@ impl Class {
                     ..
                 }) => match name.local_name.as_str() {
                     "compoundname" => self.read_compound_name(parser)?,
+                    "innerclass" => {
+                        if let Some(inner) = read_characters_only(parser)? {
+                            self.inner_classes.push(inner);
+                        }
+                    }
                     "sectiondef" => {
-                        let kind = Class::get_kind_attr(attributes)?;
+                        let kind = Class::get_kind_attr(attributes).unwrap_or_default();
                         if kind.contains("-attrib") {
-                            self.read_attributes(parser)?;
+                            self.read_attributes(parser, warnings)?;
                         } else if kind.contains("-func") {
-                            self.read_functions(parser)?;
+                            self.read_functions(parser, FunctionKind::Method, warnings)?;
+                        } else if kind.ends_with("-slot") {
+                            self.read_functions(parser, FunctionKind::Slot, warnings)?;
                         } else if kind.ends_with("-type") {
-                            self.read_types(parser)?;
-                        } else if kind == "friend" {
-                        } else if kind == "property" {
+                            self.read_types(parser, warnings)?;
                         } else if kind == "signal" {
-                            self.read_functions(parser)?;
-                        } else if kind == "related" {
+                            self.read_functions(parser, FunctionKind::Signal, warnings)?;
+                        } else if kind == "property" {
+                            self.read_properties(parser, warnings)?;
+                        } else if kind == "friend" || kind == "related" {
+                            skip_element(parser)?;
                         } else {
-                            return Err(std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                format!("Unknown kind: {}", kind),
-                            ));
+                            warnings.push(ParseWarning::new(format!(
+                                "Unknown section kind: {}, section skipped",
+                                kind
+                            )));
+                            skip_element(parser)?;
                         }
                     }
-                    "briefdescription" => self.brief = read_description(parser)?,
-                    "detaileddescription" => self.detailed = read_description(parser)?,
+                    "briefdescription" => self.brief = read_description(parser, warnings)?,
+                    "detaileddescription" => self.detailed = read_description(parser, warnings)?,
                     "location" => self.location = Some(Location::read(attributes)),
                     _ => {}
                 },
-                Ok(XmlEvent::EndElement { ref name }) => {
-                    if name.local_name == "compounddef" {
-                        break;
-                    }
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "compounddef" => {
+                    break;
                 }
                 Ok(XmlEvent::EndDocument) => break,
                 Err(e) => {
@@ -230,7 +300,12 @@ impl Class {
         Ok(())
     }
 
-    pub fn read(filename: &str, is_struct: bool) -> Result<Class, std::io::Error> {
+    pub fn read(
+        filename: &str,
+        is_struct: bool,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Class, std::io::Error> {
+        let first_warning = warnings.len();
         let file = std::io::BufReader::new(File::open(filename)?);
 
         let mut parser = EventReader::new(file);
@@ -240,10 +315,13 @@ impl Class {
 
         loop {
             match parser.next() {
-                Ok(XmlEvent::StartElement { ref name, .. }) => {
-                    if name.local_name == "compounddef" {
-                        class.read_content(&mut parser)?;
-                    }
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) if name.local_name == "compounddef" => {
+                    class.id = read_xml_attribute(attributes, "id").unwrap_or_default();
+                    class.read_content(&mut parser, warnings)?;
                 }
                 Ok(XmlEvent::EndElement { .. }) => {}
                 Ok(XmlEvent::EndDocument) => break,
@@ -257,6 +335,12 @@ impl Class {
             }
         }
 
+        for warning in warnings[first_warning..].iter_mut() {
+            if warning.location.is_none() {
+                warning.location = class.location.clone();
+            }
+        }
+
         Ok(class)
     }
 }
@@ -0,0 +1,236 @@
+use crate::check::*;
+use crate::diagnostic::*;
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+/// Places searched for the CODEOWNERS file, relative to the repository root
+const CODEOWNERS_FILES: [&str; 3] = ["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Owners of the files of a repository, read from its CODEOWNERS file
+#[derive(Debug)]
+pub struct CodeOwners {
+    rules: Vec<(Regex, Vec<String>)>,
+}
+
+/// Regular expression of a CODEOWNERS (gitignore-like) pattern, matched
+/// against paths relative to the repository root with forward slashes
+fn pattern_regex(pattern: &str) -> Option<Regex> {
+    let directory = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let mut expression = String::from(if anchored { "^" } else { "^(.*/)?" });
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    expression.push_str("(.*/)?");
+                    i += 3;
+                } else {
+                    expression.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => expression.push_str("[^/]*"),
+            '?' => expression.push_str("[^/]"),
+            c => expression.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    // A pattern naming a folder owns everything under it, while a wildcard
+    // in the last segment stops at the folder: docs/* owns docs/a.md but
+    // not docs/a/b.md
+    let last = pattern.rsplit('/').next().unwrap_or_default();
+    expression.push_str(if directory {
+        "/.*$"
+    } else if last.contains(['*', '?']) {
+        "$"
+    } else {
+        "(/.*)?$"
+    });
+    Regex::new(&expression).ok()
+}
+
+impl CodeOwners {
+    pub fn parse(content: &str) -> CodeOwners {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let pattern = match fields.next() {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+            let owners: Vec<String> = fields
+                .take_while(|field| !field.starts_with('#'))
+                .map(String::from)
+                .collect();
+            if let Some(regex) = pattern_regex(pattern) {
+                rules.push((regex, owners));
+            }
+        }
+        CodeOwners { rules }
+    }
+
+    /// CODEOWNERS of the repository rooted at `root`, if it has one
+    pub fn find(root: &Path) -> Result<Option<CodeOwners>, Error> {
+        for name in CODEOWNERS_FILES.iter() {
+            let path = root.join(name);
+            if path.is_file() {
+                return Ok(Some(CodeOwners::parse(&fs::read_to_string(path)?)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Owners of `file`, relative to the repository root: the last matching
+    /// pattern wins
+    pub fn owners(&self, file: &str) -> &[String] {
+        let file = file.replace('\\', "/");
+        match self
+            .rules
+            .iter()
+            .rev()
+            .find(|(regex, _)| regex.is_match(&file))
+        {
+            Some((_, owners)) => owners,
+            None => &[],
+        }
+    }
+}
+
+/// Report of the diagnostics without owner, `report_name` never gives it
+/// since it escapes the parentheses
+const UNOWNED_REPORT: &str = "(unowned).csv";
+
+/// File name of the report of `owner`, the characters other than ASCII
+/// letters and digits, `@`, `-`, `_` and `.` are percent-encoded so that two
+/// owners never share a report
+fn report_name(owner: &str) -> String {
+    let mut name = String::new();
+    for c in owner.chars() {
+        if c.is_ascii_alphanumeric() || "@-_.".contains(c) {
+            name.push(c);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+    format!("{}.csv", name)
+}
+
+/// Write one CSV report per owner in a folder, diagnostics without owner go
+/// to (unowned).csv
+pub struct PerOwnerErrorWriter {
+    pub folder: PathBuf,
+    pub writers: BTreeMap<String, CsvErrorWriter>,
+}
+
+impl PerOwnerErrorWriter {
+    pub fn new(folder: &str) -> Result<PerOwnerErrorWriter, Error> {
+        fs::create_dir_all(folder)?;
+        Ok(PerOwnerErrorWriter {
+            folder: PathBuf::from(folder),
+            writers: BTreeMap::new(),
+        })
+    }
+
+    fn writer(&mut self, name: String) -> Result<&mut CsvErrorWriter, Error> {
+        if !self.writers.contains_key(&name) {
+            let filename = self.folder.join(&name);
+            let writer = CsvErrorWriter::new(&filename.to_string_lossy())?;
+            self.writers.insert(name.clone(), writer);
+        }
+        Ok(self.writers.get_mut(&name).unwrap())
+    }
+}
+
+impl ErrorWriter for PerOwnerErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), Error> {
+        if diagnostic.owners.is_empty() {
+            return self
+                .writer(String::from(UNOWNED_REPORT))?
+                .append(diagnostic);
+        }
+        for owner in &diagnostic.owners {
+            self.writer(report_name(owner))?.append(diagnostic)?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "Per-owner CSV"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, file: &str) -> bool {
+        pattern_regex(pattern).unwrap().is_match(file)
+    }
+
+    #[test]
+    fn star_matches_every_file() {
+        assert!(matches("*", "README.md"));
+        assert!(matches("*", "src/ui/widget.h"));
+    }
+
+    #[test]
+    fn star_in_folder_matches_its_files_only() {
+        assert!(matches("docs/*", "docs/a.md"));
+        assert!(!matches("docs/*", "docs/a/b.md"));
+        assert!(!matches("docs/*", "src/docs/a.md"));
+        assert!(matches("*.h", "src/ui/widget.h"));
+        assert!(!matches("*.h", "src/ui/widget.hpp"));
+    }
+
+    #[test]
+    fn anchored_folder_matches_its_subtree() {
+        assert!(matches("/build/logs/", "build/logs/a.log"));
+        assert!(matches("/build/logs/", "build/logs/x/a.log"));
+        assert!(!matches("/build/logs/", "build/logs"));
+        assert!(!matches("/build/logs/", "src/build/logs/a.log"));
+    }
+
+    #[test]
+    fn double_star_matches_any_folder() {
+        assert!(matches("**/x", "x"));
+        assert!(matches("**/x", "a/b/x"));
+        assert!(matches("**/x", "a/x/y.h"));
+        assert!(!matches("**/x", "a/xy"));
+    }
+
+    #[test]
+    fn report_names_do_not_collide() {
+        assert_eq!(report_name("@org/team"), "@org%2Fteam.csv");
+        assert_eq!(report_name("@org-team"), "@org-team.csv");
+        assert_eq!(report_name("dev@example.com"), "dev@example.com.csv");
+        assert_eq!(report_name("(unowned)"), "%28unowned%29.csv");
+        assert_ne!(report_name("unowned"), UNOWNED_REPORT);
+    }
+
+    #[test]
+    fn last_matching_pattern_wins() {
+        let owners = CodeOwners::parse("* @all\n/docs/ @doc # writers\n");
+        assert_eq!(owners.owners("docs\\a.md"), ["@doc"]);
+        assert_eq!(owners.owners("src/a.h"), ["@all"]);
+    }
+}
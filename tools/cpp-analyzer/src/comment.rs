@@ -0,0 +1,888 @@
+use crate::common::*;
+use crate::description::*;
+
+/// Commands documented by doxygen, the others are reported as unknown
+const COMMANDS: [&str; 168] = [
+    "a",
+    "addindex",
+    "addtogroup",
+    "anchor",
+    "arg",
+    "attention",
+    "author",
+    "authors",
+    "b",
+    "brief",
+    "bug",
+    "c",
+    "callergraph",
+    "callgraph",
+    "category",
+    "cite",
+    "class",
+    "code",
+    "cond",
+    "copybrief",
+    "copydetails",
+    "copydoc",
+    "copyright",
+    "date",
+    "def",
+    "defgroup",
+    "deprecated",
+    "details",
+    "diafile",
+    "dir",
+    "docbookonly",
+    "dontinclude",
+    "dot",
+    "dotfile",
+    "e",
+    "else",
+    "elseif",
+    "em",
+    "emoji",
+    "endcode",
+    "endcond",
+    "enddocbookonly",
+    "enddot",
+    "endhtmlonly",
+    "endif",
+    "endinternal",
+    "endlatexonly",
+    "endlink",
+    "endmanonly",
+    "endmsc",
+    "endparblock",
+    "endrtfonly",
+    "endsecreflist",
+    "endverbatim",
+    "enduml",
+    "endxmlonly",
+    "enum",
+    "example",
+    "exception",
+    "extends",
+    "f",
+    "file",
+    "fn",
+    "headerfile",
+    "hidecallergraph",
+    "hidecallgraph",
+    "hideinitializer",
+    "hiderefby",
+    "hiderefs",
+    "htmlinclude",
+    "htmlonly",
+    "idlexcept",
+    "if",
+    "ifnot",
+    "image",
+    "implements",
+    "include",
+    "includedoc",
+    "includelineno",
+    "ingroup",
+    "internal",
+    "invariant",
+    "interface",
+    "latexinclude",
+    "latexonly",
+    "li",
+    "line",
+    "link",
+    "mainpage",
+    "manonly",
+    "memberof",
+    "msc",
+    "mscfile",
+    "n",
+    "name",
+    "namespace",
+    "nosubgrouping",
+    "note",
+    "overload",
+    "p",
+    "package",
+    "page",
+    "par",
+    "paragraph",
+    "param",
+    "parblock",
+    "post",
+    "pre",
+    "private",
+    "privatesection",
+    "property",
+    "protected",
+    "protectedsection",
+    "protocol",
+    "public",
+    "publicsection",
+    "pure",
+    "ref",
+    "refitem",
+    "related",
+    "relates",
+    "relatedalso",
+    "relatesalso",
+    "remark",
+    "remarks",
+    "result",
+    "return",
+    "returns",
+    "retval",
+    "rtfonly",
+    "sa",
+    "secreflist",
+    "section",
+    "see",
+    "short",
+    "showinitializer",
+    "showrefby",
+    "showrefs",
+    "since",
+    "skip",
+    "skipline",
+    "snippet",
+    "snippetdoc",
+    "snippetlineno",
+    "startuml",
+    "static",
+    "struct",
+    "subpage",
+    "subsection",
+    "subsubsection",
+    "tableofcontents",
+    "test",
+    "throw",
+    "throws",
+    "todo",
+    "tparam",
+    "typedef",
+    "union",
+    "until",
+    "var",
+    "verbatim",
+    "verbinclude",
+    "version",
+    "vhdlflow",
+    "warning",
+    "weakgroup",
+    "xmlonly",
+    "xrefitem",
+];
+
+/// Commands whose argument is the rest of the line and that do not add text
+const LINE_COMMANDS: [&str; 34] = [
+    "addindex",
+    "category",
+    "class",
+    "def",
+    "diafile",
+    "dir",
+    "dontinclude",
+    "dotfile",
+    "enum",
+    "example",
+    "fn",
+    "headerfile",
+    "htmlinclude",
+    "image",
+    "include",
+    "includedoc",
+    "includelineno",
+    "interface",
+    "latexinclude",
+    "line",
+    "mainpage",
+    "mscfile",
+    "name",
+    "namespace",
+    "page",
+    "paragraph",
+    "property",
+    "protocol",
+    "section",
+    "skip",
+    "skipline",
+    "snippet",
+    "struct",
+    "subsection",
+];
+
+/// Commands whose argument is a word and that do not add text
+const WORD_COMMANDS: [&str; 11] = [
+    "anchor",
+    "cond",
+    "elseif",
+    "if",
+    "ifnot",
+    "memberof",
+    "refitem",
+    "related",
+    "relatedalso",
+    "relates",
+    "relatesalso",
+];
+
+/// Commands starting a block ended by another command, with that command and
+/// whether the content of the block is kept as code
+const BLOCK_COMMANDS: [(&str, &str, bool); 10] = [
+    ("code", "endcode", true),
+    ("verbatim", "endverbatim", true),
+    ("dot", "enddot", false),
+    ("msc", "endmsc", false),
+    ("startuml", "enduml", false),
+    ("htmlonly", "endhtmlonly", false),
+    ("latexonly", "endlatexonly", false),
+    ("xmlonly", "endxmlonly", false),
+    ("manonly", "endmanonly", false),
+    ("rtfonly", "endrtfonly", false),
+];
+
+/// Documentation written in a comment block, as doxygen would read it
+#[derive(Debug, Clone, Default)]
+pub struct DocComment {
+    pub brief: Vec<Block>,
+    pub detailed: Vec<Block>,
+    /// The block documents the file with `@file`
+    pub is_file: bool,
+    /// Name and title of the group declared with `@defgroup` or `@addtogroup`
+    pub group: Option<(String, String)>,
+    /// Groups named with `@ingroup`
+    pub ingroup: Vec<String>,
+    /// The block opens a member group with `@{`
+    pub opens_group: bool,
+    /// Number of member groups closed with `@}`
+    pub closes_groups: usize,
+    /// Targets of `@copydoc`, `@copybrief` and `@copydetails`
+    pub copies: Vec<String>,
+    /// Unknown commands, with their line
+    pub unknown_commands: Vec<(String, i32)>,
+}
+
+impl DocComment {
+    pub fn is_empty(&self) -> bool {
+        self.brief.is_empty() && self.detailed.is_empty()
+    }
+
+    /// The block documents something else than the declaration following it
+    pub fn is_structural(&self) -> bool {
+        self.is_file
+            || self.group.is_some()
+            || (self.is_empty() && self.ingroup.is_empty() && self.copies.is_empty())
+    }
+
+    /// Add the documentation of a following block of the same declaration
+    pub fn append(&mut self, other: DocComment) {
+        self.brief.extend(other.brief);
+        self.detailed.extend(other.detailed);
+        self.ingroup.extend(other.ingroup);
+        self.copies.extend(other.copies);
+        self.unknown_commands.extend(other.unknown_commands);
+    }
+}
+
+/// Comment block whose delimiters are removed, each line with its number
+#[derive(Debug, Clone)]
+pub struct CommentBlock {
+    pub lines: Vec<(i32, String)>,
+    /// `///<` or `/**<` block documenting the declaration before it
+    pub trailing: bool,
+    /// Block made of `///` or `//!` lines
+    pub line_comment: bool,
+}
+
+/// Line of a `/** */` block without its leading `*`
+fn strip_decoration(line: &str) -> String {
+    match line.trim_start().strip_prefix('*') {
+        Some(rest) => String::from(rest.trim_start_matches('*')),
+        None => String::from(line),
+    }
+}
+
+impl CommentBlock {
+    /// `None` for the comments that are not doxygen blocks
+    pub fn new(text: &str, line: i32) -> Option<CommentBlock> {
+        if text.starts_with("////") {
+            return None;
+        }
+        if let Some(rest) = text
+            .strip_prefix("///")
+            .or_else(|| text.strip_prefix("//!"))
+        {
+            let (trailing, rest) = match rest.strip_prefix('<') {
+                Some(rest) => (true, rest),
+                None => (false, rest),
+            };
+            return Some(CommentBlock {
+                lines: vec![(line, String::from(rest))],
+                trailing,
+                line_comment: true,
+            });
+        }
+
+        let rest = text
+            .strip_prefix("/**")
+            .or_else(|| text.strip_prefix("/*!"))?;
+        if rest.starts_with('/') {
+            return None;
+        }
+        let rest = rest.strip_suffix("*/").unwrap_or(rest);
+        let (trailing, rest) = match rest.strip_prefix('<') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        Some(CommentBlock {
+            lines: rest
+                .lines()
+                .enumerate()
+                .map(|(index, text)| (line + index as i32, strip_decoration(text)))
+                .collect(),
+            trailing,
+            line_comment: false,
+        })
+    }
+
+    pub fn first_line(&self) -> i32 {
+        self.lines.first().map_or(0, |(line, _)| *line)
+    }
+
+    pub fn last_line(&self) -> i32 {
+        self.lines.last().map_or(0, |(line, _)| *line)
+    }
+
+    /// `next` continues this block of `///` lines
+    pub fn can_merge(&self, next: &CommentBlock) -> bool {
+        self.line_comment
+            && next.line_comment
+            && self.trailing == next.trailing
+            && next.first_line() == self.last_line() + 1
+    }
+
+    pub fn merge(&mut self, next: CommentBlock) {
+        self.lines.extend(next.lines);
+    }
+
+    /// Read the documentation of the block, a single `///` line is a brief
+    /// description like with `MULTILINE_CPP_IS_BRIEF = NO`
+    pub fn parse(&self) -> DocComment {
+        let lines = self
+            .lines
+            .iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .count();
+        let mut parser = DocParser::new(match self.line_comment && lines == 1 {
+            true => Target::Brief,
+            false => Target::Detailed,
+        });
+        for (line, text) in &self.lines {
+            parser.line(*line, text);
+        }
+        parser.finish()
+    }
+}
+
+/// Part of the documentation receiving the text being read
+enum Target {
+    Brief,
+    Detailed,
+    Section {
+        kind: String,
+        title: Option<String>,
+    },
+    Parameter {
+        kind: String,
+        names: Vec<String>,
+        direction: Option<Direction>,
+    },
+}
+
+/// Block of code or of output specific markup being read
+struct CodeBlock {
+    end: &'static str,
+    code: Option<String>,
+}
+
+struct DocParser {
+    doc: DocComment,
+    target: Target,
+    /// Content of the current section or parameter
+    blocks: Vec<Block>,
+    /// Current paragraph
+    inlines: Vec<Inline>,
+    /// Items of the current list
+    list: Option<Vec<Vec<Block>>>,
+    code: Option<CodeBlock>,
+}
+
+fn simple_section(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "return" | "returns" | "result" => "return",
+        "see" | "sa" => "see",
+        "author" | "authors" => "author",
+        "remark" | "remarks" => "remark",
+        "note" => "note",
+        "warning" => "warning",
+        "attention" => "attention",
+        "pre" => "pre",
+        "post" => "post",
+        "since" => "since",
+        "invariant" => "invariant",
+        "copyright" => "copyright",
+        "version" => "version",
+        "date" => "date",
+        "par" => "par",
+        _ => return None,
+    })
+}
+
+fn xref_section(command: &str) -> Option<(&'static str, &'static str)> {
+    match command {
+        "deprecated" => Some(("deprecated", "Deprecated")),
+        "todo" => Some(("todo", "Todo")),
+        "bug" => Some(("bug", "Bug")),
+        "test" => Some(("test", "Test")),
+        _ => None,
+    }
+}
+
+fn parameter_list(command: &str) -> Option<&'static str> {
+    match command {
+        "param" => Some("param"),
+        "tparam" => Some("templateparam"),
+        "retval" => Some("retval"),
+        "exception" | "throw" | "throws" => Some("exception"),
+        _ => None,
+    }
+}
+
+/// Next word of `chars` from `pos`, with the position following it
+fn word(chars: &[char], pos: usize) -> (String, usize) {
+    let mut start = pos;
+    while start < chars.len() && chars[start].is_whitespace() {
+        start += 1;
+    }
+    let mut end = start;
+    while end < chars.len() && !chars[end].is_whitespace() {
+        end += 1;
+    }
+    (chars[start..end].iter().collect(), end)
+}
+
+fn rest_of_line(chars: &[char], pos: usize) -> String {
+    chars[pos.min(chars.len())..]
+        .iter()
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Target of a reference written at the end of a sentence
+fn reference_target(target: &str) -> String {
+    String::from(target.trim_end_matches(['.', ',', ';', ':']))
+}
+
+impl DocParser {
+    fn new(target: Target) -> DocParser {
+        DocParser {
+            doc: DocComment::default(),
+            target,
+            blocks: Vec::new(),
+            inlines: Vec::new(),
+            list: None,
+            code: None,
+        }
+    }
+
+    fn container(&mut self) -> &mut Vec<Block> {
+        match self.target {
+            Target::Brief => &mut self.doc.brief,
+            Target::Detailed => &mut self.doc.detailed,
+            _ => &mut self.blocks,
+        }
+    }
+
+    fn push_text(&mut self, text: &mut String) {
+        let mut text = std::mem::take(text);
+        // A paragraph starts after the spaces following a command
+        if self.inlines.is_empty() {
+            text = String::from(text.trim_start());
+        }
+        if !text.is_empty() {
+            self.inlines.push(Inline::Text(text));
+        }
+    }
+
+    fn flush_paragraph(&mut self) {
+        let has_text = self.inlines.iter().any(|inline| match inline {
+            Inline::Text(text) => !text.trim().is_empty(),
+            _ => true,
+        });
+        let inlines = std::mem::take(&mut self.inlines);
+        if !has_text {
+            return;
+        }
+        let paragraph = Block::Paragraph(inlines);
+        match self.list.as_mut().and_then(|items| items.last_mut()) {
+            Some(item) => item.push(paragraph),
+            None => self.container().push(paragraph),
+        }
+    }
+
+    fn close_list(&mut self) {
+        if let Some(items) = self.list.take() {
+            self.container().push(Block::List {
+                ordered: false,
+                items,
+            });
+        }
+    }
+
+    fn list_item(&mut self) {
+        self.flush_paragraph();
+        self.list.get_or_insert_with(Vec::new).push(Vec::new());
+    }
+
+    /// Close the current section or parameter, the text that follows is
+    /// part of the detailed description
+    fn end_target(&mut self) {
+        self.flush_paragraph();
+        self.close_list();
+        match std::mem::replace(&mut self.target, Target::Detailed) {
+            Target::Section { kind, title } => {
+                let content = std::mem::take(&mut self.blocks);
+                self.doc.detailed.push(Block::Section {
+                    kind,
+                    title,
+                    content,
+                });
+            }
+            Target::Parameter {
+                kind,
+                names,
+                direction,
+            } => {
+                let item = ParameterItem {
+                    names,
+                    direction,
+                    description: std::mem::take(&mut self.blocks),
+                };
+                match self.doc.detailed.last_mut() {
+                    Some(Block::Parameters {
+                        kind: last,
+                        ref mut items,
+                    }) if *last == kind => items.push(item),
+                    _ => self.doc.detailed.push(Block::Parameters {
+                        kind,
+                        items: vec![item],
+                    }),
+                }
+            }
+            Target::Brief | Target::Detailed => {}
+        }
+    }
+
+    /// Add a line to the current code block, returns the text following its
+    /// end command if the line ends it
+    fn code_line(&mut self, text: &str) -> Option<String> {
+        let block = self.code.as_mut()?;
+        let end = ['@', '\\']
+            .iter()
+            .filter_map(|marker| text.find(&format!("{}{}", marker, block.end)))
+            .min();
+        let (content, rest) = match end {
+            Some(index) => (&text[..index], Some(&text[index + 1 + block.end.len()..])),
+            None => (text, None),
+        };
+        if let Some(code) = block.code.as_mut() {
+            if !content.trim().is_empty() || !code.is_empty() {
+                code.push_str(content);
+                code.push('\n');
+            }
+        }
+        let rest = rest?;
+        if let Some(CodeBlock {
+            code: Some(code), ..
+        }) = self.code.take()
+        {
+            let code = String::from(code.trim_end());
+            self.container().push(Block::Code(code));
+        }
+        Some(String::from(rest))
+    }
+
+    fn line(&mut self, number: i32, text: &str) {
+        let mut text = String::from(text);
+        if self.code.is_some() {
+            match self.code_line(&text) {
+                Some(rest) if !rest.trim().is_empty() => text = rest,
+                _ => return,
+            }
+        }
+
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            self.flush_paragraph();
+            self.close_list();
+            if !matches!(self.target, Target::Detailed) {
+                self.end_target();
+            }
+            return;
+        }
+        let item = ["- ", "* ", "+ ", "-# "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker));
+        let content = match item {
+            Some(content) => {
+                self.list_item();
+                content
+            }
+            None => trimmed,
+        };
+        let content = String::from(content);
+        if let Some(rest) = self.text(number, &content) {
+            // A code block starts on this line
+            if let Some(rest) = self.code_line(&rest) {
+                self.line(number, &rest);
+            }
+        }
+    }
+
+    /// Read the text and commands of a line, returns the rest of the line
+    /// when a code block starts
+    fn text(&mut self, number: i32, line: &str) -> Option<String> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut text = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let starts_word = i == 0 || chars[i - 1].is_whitespace() || chars[i - 1] == '(';
+            if (c == '@' || c == '\\') && starts_word && i + 1 < chars.len() {
+                let next = chars[i + 1];
+                if next.is_alphabetic() {
+                    let mut end = i + 1;
+                    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                        end += 1;
+                    }
+                    let name: String = chars[i + 1..end].iter().collect();
+                    self.push_text(&mut text);
+                    if let Some((_, end_command, keep)) =
+                        BLOCK_COMMANDS.iter().find(|(start, _, _)| *start == name)
+                    {
+                        self.flush_paragraph();
+                        self.code = Some(CodeBlock {
+                            end: end_command,
+                            code: match keep {
+                                true => Some(String::new()),
+                                false => None,
+                            },
+                        });
+                        // The language of the code, as in @code{.cpp}
+                        let mut rest = end;
+                        if chars.get(rest) == Some(&'{') {
+                            while rest < chars.len() && chars[rest] != '}' {
+                                rest += 1;
+                            }
+                            rest += 1;
+                        }
+                        return Some(rest_of_line(&chars, rest));
+                    }
+                    i = self.command(c, &name, &chars, end, number);
+                    continue;
+                }
+                match next {
+                    '{' => {
+                        self.doc.opens_group = true;
+                        i += 2;
+                        continue;
+                    }
+                    '}' => {
+                        self.doc.closes_groups += 1;
+                        i += 2;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            if c == '\\' && i + 1 < chars.len() && "\\@&$#<>%\".|".contains(chars[i + 1]) {
+                text.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            if c == '`' {
+                if let Some(length) = chars[i + 1..].iter().position(|&c| c == '`') {
+                    self.push_text(&mut text);
+                    let code: String = chars[i + 1..i + 1 + length].iter().collect();
+                    self.inlines.push(Inline::Code(code));
+                    i += length + 2;
+                    continue;
+                }
+            }
+            text.push(c);
+            i += 1;
+        }
+        self.push_text(&mut text);
+        None
+    }
+
+    /// Handle the command `name` ending at `pos`, returns the position
+    /// following its arguments
+    fn command(
+        &mut self,
+        marker: char,
+        name: &str,
+        chars: &[char],
+        pos: usize,
+        line: i32,
+    ) -> usize {
+        if name == "brief" || name == "short" {
+            self.end_target();
+            self.target = Target::Brief;
+            return pos;
+        }
+        if name == "details" {
+            self.end_target();
+            return pos;
+        }
+        if let Some(kind) = parameter_list(name) {
+            self.end_target();
+            let mut pos = pos;
+            let mut direction = None;
+            if chars.get(pos) == Some(&'[') {
+                let end = chars[pos..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map_or(chars.len(), |length| pos + length);
+                let text: String = chars[pos + 1..end]
+                    .iter()
+                    .filter(|c| c.is_alphabetic())
+                    .collect();
+                direction = direction_from_str(&text);
+                pos = (end + 1).min(chars.len());
+            }
+            let (names, pos) = word(chars, pos);
+            self.target = Target::Parameter {
+                kind: String::from(kind),
+                names: names
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect(),
+                direction,
+            };
+            return pos;
+        }
+        if let Some(kind) = simple_section(name) {
+            self.end_target();
+            let title = match kind {
+                "par" => Some(rest_of_line(chars, pos)),
+                _ => None,
+            };
+            self.target = Target::Section {
+                kind: String::from(kind),
+                title,
+            };
+            return match kind {
+                "par" => chars.len(),
+                _ => pos,
+            };
+        }
+        if let Some((kind, title)) = xref_section(name) {
+            self.end_target();
+            self.target = Target::Section {
+                kind: String::from(kind),
+                title: Some(String::from(title)),
+            };
+            return pos;
+        }
+
+        match name {
+            "c" | "p" => {
+                let (word, pos) = word(chars, pos);
+                self.inlines.push(Inline::Code(word));
+                pos
+            }
+            "a" | "b" | "e" | "em" | "cite" | "emoji" => {
+                let (word, pos) = word(chars, pos);
+                self.inlines.push(Inline::Text(word));
+                pos
+            }
+            "copydoc" | "copybrief" | "copydetails" => {
+                let (target, pos) = word(chars, pos);
+                self.doc.copies.push(reference_target(&target));
+                pos
+            }
+            "ref" | "link" => {
+                let (target, mut pos) = word(chars, pos);
+                // The text of the link replaces its target
+                let quote = chars[pos..].iter().position(|c| !c.is_whitespace());
+                if name == "ref" && quote.map(|offset| chars[pos + offset]) == Some('"') {
+                    let start = pos + quote.unwrap_or_default() + 1;
+                    pos = chars[start..]
+                        .iter()
+                        .position(|&c| c == '"')
+                        .map_or(chars.len(), |length| start + length + 1);
+                }
+                self.inlines.push(Inline::Ref(Reference {
+                    kind: ReferenceKind::Ref,
+                    refid: None,
+                    kindref: None,
+                    text: reference_target(&target),
+                }));
+                pos
+            }
+            "n" => {
+                self.inlines.push(Inline::Text(String::from("\n")));
+                pos
+            }
+            "li" | "arg" => {
+                self.list_item();
+                pos
+            }
+            "file" => {
+                self.doc.is_file = true;
+                let (file, end) = word(chars, pos);
+                match file.starts_with(['@', '\\']) {
+                    true => pos,
+                    false => end,
+                }
+            }
+            "defgroup" | "addtogroup" | "weakgroup" => {
+                let (group, pos) = word(chars, pos);
+                self.doc.group = Some((group, rest_of_line(chars, pos)));
+                chars.len()
+            }
+            "ingroup" => {
+                let groups = rest_of_line(chars, pos);
+                self.doc
+                    .ingroup
+                    .extend(groups.split_whitespace().map(String::from));
+                chars.len()
+            }
+            _ if LINE_COMMANDS.contains(&name) => chars.len(),
+            _ if WORD_COMMANDS.contains(&name) => word(chars, pos).1,
+            _ if COMMANDS.contains(&name) => pos,
+            _ => {
+                let command = format!("{}{}", marker, name);
+                self.doc.unknown_commands.push((command.clone(), line));
+                self.inlines.push(Inline::Text(command));
+                pos
+            }
+        }
+    }
+
+    fn finish(mut self) -> DocComment {
+        if let Some(CodeBlock {
+            code: Some(code), ..
+        }) = self.code.take()
+        {
+            self.flush_paragraph();
+            let code = String::from(code.trim_end());
+            self.container().push(Block::Code(code));
+        }
+        self.end_target();
+        self.doc
+    }
+}
@@ -1,3 +1,6 @@
+use crate::description::*;
+
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
 use xml::{
@@ -5,7 +8,7 @@ use xml::{
     reader::{EventReader, XmlEvent},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Access {
     Unknown,
     Public,
@@ -13,25 +16,132 @@ pub enum Access {
     Private,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     In,
     Out,
     InOut,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Location {
     pub file: String,
     pub line: i32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: Option<String>,
     pub ctype: Option<String>,
-    pub description: Option<String>,
+    pub description: Option<Description>,
     pub direction: Option<Direction>,
+    /// Default argument, as written in the declaration
+    #[serde(default)]
+    pub default_value: Option<String>,
+}
+
+/// Anomaly found in the doxygen XML while reading an entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseWarning {
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl ParseWarning {
+    pub fn new(message: String) -> ParseWarning {
+        ParseWarning {
+            message,
+            location: Option::None,
+        }
+    }
+}
+
+impl Default for Parameter {
+    fn default() -> Self {
+        Parameter::new()
+    }
+}
+
+/// Code of a character in doxygen ids and file names, `None` for the
+/// characters written as they are
+pub fn escaped_char(c: char) -> Option<&'static str> {
+    match c {
+        '_' => Some("__"),
+        ':' => Some("_1"),
+        '/' => Some("_2"),
+        '<' => Some("_3"),
+        '>' => Some("_4"),
+        '*' => Some("_5"),
+        '&' => Some("_6"),
+        '|' => Some("_7"),
+        '.' => Some("_8"),
+        '!' => Some("_9"),
+        ',' => Some("_00"),
+        ' ' => Some("_01"),
+        '{' => Some("_02"),
+        '}' => Some("_03"),
+        '?' => Some("_04"),
+        '^' => Some("_05"),
+        '%' => Some("_06"),
+        '(' => Some("_07"),
+        ')' => Some("_08"),
+        '+' => Some("_09"),
+        '=' => Some("_0a"),
+        '$' => Some("_0b"),
+        '\\' => Some("_0c"),
+        '@' => Some("_0d"),
+        ']' => Some("_0e"),
+        '[' => Some("_0f"),
+        '#' => Some("_0g"),
+        '"' => Some("_0h"),
+        '~' => Some("_0i"),
+        '\'' => Some("_0j"),
+        ';' => Some("_0k"),
+        '`' => Some("_0l"),
+        _ => None,
+    }
+}
+
+/// Split a qualified name into its scope and its unqualified name, ignoring
+/// the `::` found in template arguments
+pub fn split_scope(name: &str) -> (&str, &str) {
+    let bytes = name.as_bytes();
+    let mut depth = 0;
+    let mut last: Option<usize> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            b'>' if depth > 0 => depth -= 1,
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                last = Some(i);
+                i += 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    match last {
+        Some(pos) => (&name[..pos], &name[pos + 2..]),
+        None => ("", name),
+    }
+}
+
+/// Give `location` to the warnings raised since `first` and mention that
+/// `entity` is skipped, returns true if there is any
+pub fn skip_on_warnings(
+    warnings: &mut [ParseWarning],
+    first: usize,
+    entity: &str,
+    location: &Option<Location>,
+) -> bool {
+    for warning in warnings[first..].iter_mut() {
+        warning.message = format!("{}, {} skipped", warning.message, entity);
+        if warning.location.is_none() {
+            warning.location = location.clone();
+        }
+    }
+    warnings.len() > first
 }
 
 impl Parameter {
@@ -41,6 +151,7 @@ impl Parameter {
             ctype: Option::None,
             description: Option::None,
             direction: Option::None,
+            default_value: Option::None,
         }
     }
 }
@@ -56,7 +167,7 @@ pub fn read_characters_only(
                 //if depth == 0 {
                 if !chars.is_empty() {
                     match a {
-                        Some(val) => a = Some(format!("{} {}", val, chars.to_string())),
+                        Some(val) => a = Some(format!("{} {}", val, chars)),
                         None => a = Some(chars.to_string().clone()),
                     }
                 }
@@ -71,12 +182,22 @@ pub fn read_characters_only(
                 }
                 depth -= 1;
             }
-            Ok(XmlEvent::CData(ref s)) => println!("CDATA {}", s),
-            Ok(XmlEvent::Comment(ref s)) => println!("Comment {}", s),
-            Ok(XmlEvent::Whitespace(..)) => {} // println!("Whitespace {}", s),
-            Ok(XmlEvent::EndDocument) => println!("end doc"),
-            Ok(XmlEvent::ProcessingInstruction { ref name, ref data }) => {
-                println!("Processing instruction {} {:?}", name, data)
+            Ok(XmlEvent::CData(ref chars)) => {
+                if !chars.is_empty() {
+                    match a {
+                        Some(val) => a = Some(format!("{} {}", val, chars)),
+                        None => a = Some(chars.clone()),
+                    }
+                }
+            }
+            Ok(XmlEvent::Comment(..)) => {}
+            Ok(XmlEvent::Whitespace(..)) => {}
+            Ok(XmlEvent::ProcessingInstruction { .. }) => {}
+            Ok(XmlEvent::EndDocument) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "Unexpected end of document",
+                ))
             }
             Err(e) => {
                 return Err(std::io::Error::new(
@@ -96,41 +217,53 @@ pub fn read_characters_only(
     Ok(a)
 }
 
-pub fn read_description(
-    parser: &mut EventReader<BufReader<File>>,
-) -> Result<Option<String>, std::io::Error> {
-    let mut a: Option<String> = Option::None;
+/// Kind of the compound described in a doxygen XML file, `None` for files
+/// without compound such as index.xml
+pub fn read_compound_kind(filename: &str) -> Result<Option<String>, std::io::Error> {
+    let file = BufReader::new(File::open(filename)?);
+    let parser = EventReader::new(file);
+    for event in parser {
+        match event {
+            Ok(XmlEvent::StartElement {
+                ref name,
+                ref attributes,
+                ..
+            }) if name.local_name == "compounddef" => {
+                return Ok(read_xml_attribute(attributes, "kind"));
+            }
+            Ok(XmlEvent::StartElement { ref name, .. }) if name.local_name != "doxygen" => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Consume the events up to the end of the current element
+pub fn skip_element(parser: &mut EventReader<BufReader<File>>) -> Result<(), std::io::Error> {
     let mut depth = 0;
     loop {
         match parser.next() {
-            Ok(XmlEvent::StartElement { ref name, .. }) => {
-                if name.local_name == "para" {
-                    let read = read_characters_only(parser)?;
-                    if read.is_some() {
-                        match a {
-                            Some(val) => a = Some(format!("{}\n{}", val, read.unwrap())),
-                            None => a = read,
-                        }
-                    }
-                } else {
-                    depth += 1;
-                }
-            }
-            Ok(XmlEvent::EndElement { ref name }) => {
+            Ok(XmlEvent::StartElement { .. }) => depth += 1,
+            Ok(XmlEvent::EndElement { .. }) => {
                 if depth == 0 {
-                    if name.local_name != "detaileddescription"
-                        && name.local_name != "briefdescription"
-                        && name.local_name != "parameterdescription"
-                    {
-                        println!(
-                            "Inconsistency {}, expected briefdescription or detailed description",
-                            name.local_name.as_str()
-                        );
-                    }
                     break;
                 }
                 depth -= 1;
             }
+            Ok(XmlEvent::EndDocument) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "Unexpected end of document",
+                ))
+            }
             Err(e) => {
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
@@ -140,11 +273,11 @@ pub fn read_description(
             _ => {}
         }
     }
-    Ok(a)
+    Ok(())
 }
 
 impl Location {
-    pub fn read(attributes: &Vec<OwnedAttribute>) -> Location {
+    pub fn read(attributes: &[OwnedAttribute]) -> Location {
         let line = match attributes.iter().find(|&r| r.name.local_name == "line") {
             Some(val) => val.value.to_string().parse::<i32>().unwrap_or(-2),
             None => -1,
@@ -155,18 +288,15 @@ impl Location {
             None => String::new(),
         };
 
-        Location {
-            file: file,
-            line: line,
-        }
+        Location { file, line }
     }
 }
 
-pub fn read_xml_attribute(attributes: &Vec<OwnedAttribute>, key: &str) -> Option<String> {
-    match attributes.iter().find(|&r| r.name.local_name == key) {
-        Some(val) => Option::Some(val.value.to_string().clone()),
-        None => Option::None,
-    }
+pub fn read_xml_attribute(attributes: &[OwnedAttribute], key: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|&r| r.name.local_name == key)
+        .map(|val| val.value.to_string().clone())
 }
 
 pub fn direction_from_str(text: &str) -> Option<Direction> {
@@ -0,0 +1,291 @@
+use crate::common::*;
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufReader, Error};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct CompileCommand {
+    directory: String,
+    file: String,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    arguments: Option<Vec<String>>,
+}
+
+/// Files and preprocessor context of a `compile_commands.json`
+#[derive(Debug, Default)]
+pub struct CompilationDatabase {
+    /// Compiled sources and the headers they include, canonical paths
+    pub files: Vec<String>,
+    /// `-D` defines, as `NAME` or `NAME=value`
+    pub defines: Vec<String>,
+    /// `-I` include paths, canonical paths
+    pub include_paths: Vec<String>,
+}
+
+/// Split a command line, honouring double quotes and backslash escapes
+fn split_command(command: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quoted = false;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_argument = true;
+            }
+            '\\' if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                current.push(chars.next().unwrap());
+                in_argument = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_argument = true;
+            }
+        }
+    }
+    if in_argument {
+        arguments.push(current);
+    }
+    arguments
+}
+
+/// Compilers accepting `/D` and `/I`, where `/` does not start a path
+fn is_msvc(compiler: &str) -> bool {
+    match Path::new(compiler).file_stem() {
+        Some(stem) => ["cl", "clang-cl"].contains(&stem.to_string_lossy().to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+/// Value of an option given as `-Xvalue` or `-X value`, or with a `/` for
+/// MSVC
+fn option_value<'a, I: Iterator<Item = &'a String>>(
+    argument: &'a str,
+    flag: char,
+    msvc: bool,
+    arguments: &mut I,
+) -> Option<&'a str> {
+    let mut chars = argument.chars();
+    match (chars.next(), chars.next()) {
+        (Some('-'), Some(f)) if f == flag => {}
+        (Some('/'), Some(f)) if f == flag && msvc => {}
+        _ => return None,
+    }
+    let value = &argument[2..];
+    if value.is_empty() {
+        arguments.next().map(|value| value.as_str())
+    } else {
+        Some(value)
+    }
+}
+
+/// Canonical path without the `\\?\` prefix added on Windows, so that the
+/// files compare and print like the paths given by the user
+fn canonicalize(path: PathBuf) -> PathBuf {
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    let text = path.to_string_lossy();
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", share))
+    } else if let Some(local) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        path
+    }
+}
+
+fn canonical(directory: &Path, path: &str) -> PathBuf {
+    canonicalize(directory.join(path))
+}
+
+/// Headers included with `#include "..."` or `#include <...>` by `file`,
+/// searched next to it then in the include paths
+fn included_files(file: &Path, include_paths: &[PathBuf]) -> Vec<PathBuf> {
+    let text = match fs::read(file) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(_) => return Vec::new(),
+    };
+    let mut included = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_start();
+        let directive = match line.strip_prefix('#') {
+            Some(directive) => directive.trim_start(),
+            None => continue,
+        };
+        let name = match directive.strip_prefix("include") {
+            Some(name) => name.trim(),
+            None => continue,
+        };
+        let (name, local) = match name.chars().next() {
+            Some('"') => (name[1..].split('"').next(), true),
+            Some('<') => (name[1..].split('>').next(), false),
+            _ => continue,
+        };
+        let name = match name {
+            Some(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+        let local_folder = file.parent().filter(|_| local).map(Path::to_path_buf);
+        if let Some(found) = local_folder
+            .iter()
+            .chain(include_paths.iter())
+            .map(|folder| folder.join(name))
+            .find(|candidate| candidate.is_file())
+        {
+            included.push(canonicalize(found));
+        }
+    }
+    included
+}
+
+impl CompilationDatabase {
+    pub fn read(
+        filename: &str,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<CompilationDatabase, Error> {
+        let file = BufReader::new(File::open(filename)?);
+        let commands: Vec<CompileCommand> = serde_json::from_reader(file)?;
+
+        let mut database = CompilationDatabase::default();
+        let mut include_paths: Vec<PathBuf> = Vec::new();
+        let mut sources: Vec<PathBuf> = Vec::new();
+        let mut conflicts: HashSet<String> = HashSet::new();
+        for command in &commands {
+            let directory = Path::new(&command.directory);
+            let arguments = match (&command.arguments, &command.command) {
+                (Some(arguments), _) => arguments.clone(),
+                (None, Some(command)) => split_command(command),
+                (None, None) => Vec::new(),
+            };
+            let msvc = arguments.first().is_some_and(|compiler| is_msvc(compiler));
+            let mut iter = arguments.iter();
+            while let Some(argument) = iter.next() {
+                if let Some(define) = option_value(argument, 'D', msvc, &mut iter) {
+                    // The first definition wins, a single context is analysed
+                    let name = define.split('=').next().unwrap_or(define);
+                    match database
+                        .defines
+                        .iter()
+                        .find(|d| d.split('=').next() == Some(name))
+                    {
+                        Some(first) if first != define => {
+                            if conflicts.insert(String::from(define)) {
+                                warnings.push(ParseWarning::new(format!(
+                                    "{} of {} conflicts with {} of another file, {} kept",
+                                    define, command.file, first, first
+                                )));
+                            }
+                        }
+                        Some(_) => {}
+                        None => database.defines.push(String::from(define)),
+                    }
+                } else if let Some(path) = option_value(argument, 'I', msvc, &mut iter) {
+                    let path = canonical(directory, path);
+                    if !include_paths.contains(&path) {
+                        include_paths.push(path);
+                    }
+                }
+            }
+            let source = canonical(directory, &command.file);
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+
+        // Only the headers of the include paths and next to the sources are
+        // followed, system headers are not analysed
+        let mut seen: HashSet<PathBuf> = sources.iter().cloned().collect();
+        let mut pending = sources.clone();
+        while let Some(file) = pending.pop() {
+            for header in included_files(&file, &include_paths) {
+                if seen.insert(header.clone()) {
+                    pending.push(header);
+                }
+            }
+        }
+
+        let mut files: Vec<String> = seen
+            .into_iter()
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        database.files = files;
+        database.include_paths = include_paths
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        Ok(database)
+    }
+
+    /// Files located under one of `folders`
+    pub fn files_in(&self, folders: &[String]) -> Vec<String> {
+        let folders: Vec<PathBuf> = folders
+            .iter()
+            .filter(|f| Path::new(f).exists())
+            .map(|f| canonicalize(PathBuf::from(f)))
+            .collect();
+        self.files
+            .iter()
+            .filter(|file| {
+                folders
+                    .iter()
+                    .any(|folder| Path::new(file).starts_with(folder))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbatim_prefix_is_removed() {
+        assert_eq!(
+            canonicalize(PathBuf::from(r"\\?\C:\src\a.cpp")),
+            PathBuf::from(r"C:\src\a.cpp")
+        );
+        assert_eq!(
+            canonicalize(PathBuf::from(r"\\?\UNC\server\src\a.cpp")),
+            PathBuf::from(r"\\server\src\a.cpp")
+        );
+    }
+
+    #[test]
+    fn conflicting_defines_are_reported() {
+        let filename = std::env::temp_dir().join("cpp-analyzer-conflicting-defines.json");
+        fs::write(
+            &filename,
+            r#"[
+                { "directory": "/src", "file": "a.cpp", "command": "c++ -DLEVEL=1 -DSDK a.cpp" },
+                { "directory": "/src", "file": "b.cpp", "command": "c++ -DLEVEL=2 -DSDK b.cpp" },
+                { "directory": "/src", "file": "c.cpp", "command": "c++ -DLEVEL=2 c.cpp" }
+            ]"#,
+        )
+        .unwrap();
+        let mut warnings = Vec::new();
+        let database =
+            CompilationDatabase::read(&filename.to_string_lossy(), &mut warnings).unwrap();
+        fs::remove_file(&filename).unwrap();
+
+        assert_eq!(database.defines, ["LEVEL=1", "SDK"]);
+        let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            ["LEVEL=2 of b.cpp conflicts with LEVEL=1 of another file, LEVEL=1 kept"]
+        );
+    }
+}
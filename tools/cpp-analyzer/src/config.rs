@@ -0,0 +1,182 @@
+use crate::diagnostic::*;
+use crate::doxygen;
+use crate::project::*;
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE: &str = ".cpp-analyzer.toml";
+
+/// Settings shared by a project, read from a `.cpp-analyzer.toml` file
+///
+/// ```toml
+/// input = ["include", "src"]
+/// exclude = ["*/detail/*"]
+/// doxygen = "/usr/bin/doxygen"
+/// frontend = "builtin"
+/// export_macros = ["SDK_EXPORT"]
+/// compile_commands = "build/compile_commands.json"
+/// output = ["vs", "sarif:report.sarif", "report.csv"]
+/// group_namespaces = ["sdk"]
+/// license = "Copyright \\(c\\) [0-9]{4} ACME"
+/// macro_prefix = "SDK_"
+/// deprecated_macros = ["SDK_DEPRECATED"]
+/// qt = true
+/// header_case = "snake"
+/// max_classes_per_header = 1
+/// source_root = "."
+/// attribute = true
+/// per_owner = "reports/owners"
+/// history = "reports/history.jsonl"
+///
+/// [path_map]
+/// "/builds/sdk" = "C:/dev/sdk"
+///
+/// [metric_limits]
+/// parameters = 6
+/// public-methods = 30
+///
+/// [rules]
+/// parameter-name = "off"
+/// class-description = "error"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Folder of the configuration file, relative inputs are resolved from it
+    #[serde(skip)]
+    pub folder: PathBuf,
+    pub input: Vec<String>,
+    pub exclude: Vec<String>,
+    pub doxygen: Option<String>,
+    /// Parser of the sources, doxygen unless set to "builtin"
+    pub frontend: Option<Frontend>,
+    /// Export macros of the class declarations, for the built-in parser
+    pub export_macros: Vec<String>,
+    pub compile_commands: Option<String>,
+    pub output: Vec<String>,
+    /// Namespaces whose public classes must belong to a doxygen group
+    pub group_namespaces: Vec<String>,
+    /// Regular expression matching the license header of the sources
+    pub license: Option<String>,
+    pub macro_prefix: Option<String>,
+    /// Macros marking deprecated declarations besides `DEPRECATED`
+    pub deprecated_macros: Vec<String>,
+    /// Check the Qt properties and signals
+    pub qt: bool,
+    /// Expected file name of class headers: exact, lower or snake
+    pub header_case: Option<String>,
+    pub max_classes_per_header: Option<usize>,
+    /// Folder the reported locations are relative to
+    pub source_root: Option<String>,
+    /// Prefixes replaced in the reported locations
+    pub path_map: BTreeMap<String, String>,
+    /// Give the git blame author and CODEOWNERS owners of the diagnostics
+    pub attribute: bool,
+    /// Folder receiving one report per owner
+    pub per_owner: Option<String>,
+    /// JSON Lines file receiving the statistics of each run
+    pub history: Option<String>,
+    /// Outlier thresholds, by metric name
+    pub metric_limits: BTreeMap<String, usize>,
+    /// Severity of each rule, by rule id
+    pub rules: BTreeMap<String, String>,
+}
+
+impl Config {
+    pub fn read(filename: &Path) -> Result<Config, Error> {
+        let text = fs::read_to_string(filename)?;
+        let mut config: Config = toml::from_str(&text)
+            .map_err(|e| Error::other(format!("{}: {}", filename.display(), e)))?;
+        config.folder = match filename.parent() {
+            Some(folder) if !folder.as_os_str().is_empty() => folder.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        Ok(config)
+    }
+
+    /// Configuration file of the first input that has one in its folder or
+    /// in one of its parents
+    pub fn discover(inputs: &[String]) -> Option<PathBuf> {
+        for input in inputs {
+            let path = match fs::canonicalize(input) {
+                Ok(path) => path,
+                Err(_) => continue,
+            };
+            let folder = if path.is_dir() {
+                path.as_path()
+            } else {
+                match path.parent() {
+                    Some(folder) => folder,
+                    None => continue,
+                }
+            };
+            if let Some(found) = folder
+                .ancestors()
+                .map(|ancestor| ancestor.join(CONFIG_FILE))
+                .find(|candidate| candidate.is_file())
+            {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Input folders, relative to the current directory
+    pub fn inputs(&self) -> Vec<String> {
+        self.input
+            .iter()
+            .map(|input| self.folder.join(input).to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Compilation database, relative to the current directory
+    pub fn compile_commands(&self) -> Option<String> {
+        self.compile_commands
+            .as_ref()
+            .map(|path| self.folder.join(path).to_string_lossy().into_owned())
+    }
+
+    /// Source root, relative to the current directory
+    pub fn source_root(&self) -> Option<String> {
+        self.source_root
+            .as_ref()
+            .map(|path| self.folder.join(path).to_string_lossy().into_owned())
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            frontend: self.frontend.unwrap_or_default(),
+            doxygen: match &self.doxygen {
+                // A bare name is looked up in the PATH
+                Some(doxygen) if doxygen.contains('/') || doxygen.contains('\\') => {
+                    self.folder.join(doxygen).to_string_lossy().into_owned()
+                }
+                Some(doxygen) => doxygen.clone(),
+                None => String::from(doxygen::DEFAULT_DOXYGEN),
+            },
+            exclude: self.exclude.clone(),
+            export_macros: self.export_macros.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn severities(&self) -> Result<HashMap<Rule, Severity>, String> {
+        let mut severities = HashMap::new();
+        for (id, name) in &self.rules {
+            let rule = match Rule::from_id(id) {
+                Some(rule) => rule,
+                None => return Err(format!("unknown rule {}", id)),
+            };
+            let severity = match Severity::from_name(name) {
+                Some(severity) => severity,
+                None => return Err(format!("unknown severity {} for {}", name, id)),
+            };
+            severities.insert(rule, severity);
+        }
+        Ok(severities)
+    }
+}
@@ -0,0 +1,619 @@
+use crate::common::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use xml::attribute::OwnedAttribute;
+use xml::reader::{EventReader, XmlEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReferenceKind {
+    Ref,
+    See,
+}
+
+/// Link found in a description, `refid` is `None` when doxygen could not
+/// resolve the target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    pub refid: Option<String>,
+    pub kindref: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Inline {
+    Text(String),
+    Code(String),
+    Ref(Reference),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterItem {
+    pub names: Vec<String>,
+    pub direction: Option<Direction>,
+    pub description: Vec<Block>,
+}
+
+/// Element of the doxygen description markup, `Section` holds the
+/// `simplesect` (note, warning, see, return...) and `xrefsect` (deprecated,
+/// todo...) parts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    Code(String),
+    List {
+        ordered: bool,
+        items: Vec<Vec<Block>>,
+    },
+    Section {
+        kind: String,
+        title: Option<String>,
+        content: Vec<Block>,
+    },
+    Parameters {
+        kind: String,
+        items: Vec<ParameterItem>,
+    },
+}
+
+/// Description with its markup in `blocks`, the other fields summarize it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredDescription")]
+pub struct Description {
+    pub text: String,
+    pub references: Vec<Reference>,
+    pub deprecated: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+/// Model dumps written before references were kept store the text only
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredDescription {
+    Text(String),
+    Full {
+        text: String,
+        #[serde(default)]
+        references: Vec<Reference>,
+        #[serde(default)]
+        deprecated: Option<String>,
+        #[serde(default)]
+        blocks: Vec<Block>,
+    },
+}
+
+impl From<StoredDescription> for Description {
+    fn from(stored: StoredDescription) -> Description {
+        match stored {
+            StoredDescription::Text(text) => Description {
+                text,
+                ..Default::default()
+            },
+            StoredDescription::Full {
+                text,
+                references,
+                deprecated,
+                blocks,
+            } => Description {
+                text,
+                references,
+                deprecated,
+                blocks,
+            },
+        }
+    }
+}
+
+/// Whether `text` names an entity, as in `Widget::show()` or `Widget#show`
+pub fn is_identifier_target(text: &str) -> bool {
+    let text = text.trim().trim_end_matches(['.', ',', ';', ':']);
+    let name = match text.find('(') {
+        Some(start) if text.ends_with(')') => &text[..start],
+        Some(_) => return false,
+        None => text,
+    };
+    let name = name.trim_start_matches("::").replace('#', "::");
+    !name.is_empty()
+        && name.split("::").all(|part| {
+            let mut chars = part.chars();
+            matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '~')
+                && chars.all(|c| c.is_alphanumeric() || c == '_')
+        })
+}
+
+fn append(text: &mut String, other: &str, separator: &str) {
+    if other.is_empty() {
+        return;
+    }
+    if !text.is_empty() {
+        text.push_str(separator);
+    }
+    text.push_str(other);
+}
+
+impl Description {
+    pub fn from_blocks(blocks: Vec<Block>) -> Description {
+        let mut description = Description::default();
+        for block in &blocks {
+            let mut text = String::new();
+            description.summarize(block, ReferenceKind::Ref, &mut text);
+            append(&mut description.text, &text, "\n");
+        }
+        description.blocks = blocks;
+        description
+    }
+
+    fn summarize(&mut self, block: &Block, kind: ReferenceKind, text: &mut String) {
+        match block {
+            Block::Paragraph(inlines) => {
+                for inline in inlines {
+                    match inline {
+                        Inline::Text(chars) | Inline::Code(chars) => append(text, chars, " "),
+                        Inline::Ref(reference) => {
+                            append(text, &reference.text, " ");
+                            self.references.push(Reference {
+                                kind,
+                                ..reference.clone()
+                            });
+                        }
+                    }
+                }
+            }
+            Block::Code(code) => append(text, code, " "),
+            Block::List { items, .. } => {
+                for block in items.iter().flatten() {
+                    self.summarize(block, kind, text);
+                }
+            }
+            Block::Section {
+                kind: section,
+                content,
+                ..
+            } => {
+                let mut section_text = String::new();
+                let first = self.references.len();
+                let kind = match section.as_str() {
+                    "see" => ReferenceKind::See,
+                    _ => kind,
+                };
+                for block in content {
+                    self.summarize(block, kind, &mut section_text);
+                }
+                // A see also naming an entity doxygen could not link, prose
+                // and URLs are not references
+                if kind == ReferenceKind::See
+                    && self.references.len() == first
+                    && is_identifier_target(&section_text)
+                {
+                    self.references.push(Reference {
+                        kind,
+                        refid: None,
+                        kindref: None,
+                        text: String::from(section_text.trim()),
+                    });
+                }
+                if section == "deprecated" {
+                    self.deprecated = Some(
+                        section_text
+                            .split_whitespace()
+                            .collect::<Vec<&str>>()
+                            .join(" "),
+                    );
+                }
+                append(text, &section_text, " ");
+            }
+            Block::Parameters { items, .. } => {
+                for block in items.iter().flat_map(|item| item.description.iter()) {
+                    self.summarize(block, kind, text);
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.references.is_empty() && self.deprecated.is_none()
+    }
+}
+
+pub fn is_deprecated(brief: &Option<Description>, detailed: &Option<Description>) -> bool {
+    brief
+        .iter()
+        .chain(detailed.iter())
+        .any(|d| d.deprecated.is_some())
+}
+
+fn unexpected_end() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        "Unexpected end of document",
+    )
+}
+
+pub fn read_reference(
+    parser: &mut EventReader<BufReader<File>>,
+    attributes: &[OwnedAttribute],
+) -> Result<Reference, std::io::Error> {
+    Ok(Reference {
+        kind: ReferenceKind::Ref,
+        refid: read_xml_attribute(attributes, "refid"),
+        kindref: read_xml_attribute(attributes, "kindref"),
+        text: read_characters_only(parser)?.unwrap_or_default(),
+    })
+}
+
+/// Read a `programlisting`, keeping its lines and spaces
+fn read_code(parser: &mut EventReader<BufReader<File>>) -> Result<String, std::io::Error> {
+    let mut code = String::new();
+    let mut depth = 0;
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::Characters(ref chars)) | Ok(XmlEvent::CData(ref chars)) => {
+                code.push_str(chars)
+            }
+            Ok(XmlEvent::StartElement { ref name, .. }) => {
+                if name.local_name == "sp" {
+                    code.push(' ');
+                }
+                depth += 1;
+            }
+            Ok(XmlEvent::EndElement { ref name }) => {
+                if depth == 0 {
+                    break;
+                }
+                if name.local_name == "codeline" {
+                    code.push('\n');
+                }
+                depth -= 1;
+            }
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(String::from(code.trim_end()))
+}
+
+fn read_list(
+    parser: &mut EventReader<BufReader<File>>,
+    ordered: bool,
+) -> Result<Block, std::io::Error> {
+    let mut items = Vec::new();
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement { ref name, .. }) => match name.local_name.as_str() {
+                "listitem" => items.push(read_blocks(parser)?),
+                _ => skip_element(parser)?,
+            },
+            Ok(XmlEvent::EndElement { .. }) => break,
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(Block::List { ordered, items })
+}
+
+fn read_xrefsect(
+    parser: &mut EventReader<BufReader<File>>,
+    attributes: &[OwnedAttribute],
+) -> Result<Block, std::io::Error> {
+    // Ids look like deprecated_1_deprecated000001
+    let id = read_xml_attribute(attributes, "id").unwrap_or_default();
+    let kind = String::from(id.split("_1").next().unwrap_or_default());
+    let mut title = None;
+    let mut content = Vec::new();
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement { ref name, .. }) => match name.local_name.as_str() {
+                "xreftitle" => title = read_characters_only(parser)?,
+                "xrefdescription" => content = read_blocks(parser)?,
+                _ => skip_element(parser)?,
+            },
+            Ok(XmlEvent::EndElement { .. }) => break,
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(Block::Section {
+        kind,
+        title,
+        content,
+    })
+}
+
+fn read_parameter_item(
+    parser: &mut EventReader<BufReader<File>>,
+) -> Result<ParameterItem, std::io::Error> {
+    let mut item = ParameterItem {
+        names: Vec::new(),
+        direction: None,
+        description: Vec::new(),
+    };
+    let mut depth = 0;
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement {
+                ref name,
+                ref attributes,
+                ..
+            }) => match name.local_name.as_str() {
+                "parametername" => {
+                    if let Some(direction) = read_xml_attribute(attributes, "direction") {
+                        item.direction = direction_from_str(direction.as_str());
+                    }
+                    if let Some(name) = read_characters_only(parser)? {
+                        item.names.push(name);
+                    }
+                }
+                "parameterdescription" => item.description = read_blocks(parser)?,
+                _ => depth += 1,
+            },
+            Ok(XmlEvent::EndElement { .. }) => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(item)
+}
+
+fn read_parameters(
+    parser: &mut EventReader<BufReader<File>>,
+    attributes: &[OwnedAttribute],
+) -> Result<Block, std::io::Error> {
+    let kind = read_xml_attribute(attributes, "kind").unwrap_or_default();
+    let mut items = Vec::new();
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement { ref name, .. }) => match name.local_name.as_str() {
+                "parameteritem" => items.push(read_parameter_item(parser)?),
+                _ => skip_element(parser)?,
+            },
+            Ok(XmlEvent::EndElement { .. }) => break,
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(Block::Parameters { kind, items })
+}
+
+fn flush(inlines: &mut Vec<Inline>, blocks: &mut Vec<Block>) {
+    let has_text = inlines.iter().any(|inline| match inline {
+        Inline::Text(text) => !text.trim().is_empty(),
+        _ => true,
+    });
+    if has_text {
+        blocks.push(Block::Paragraph(std::mem::take(inlines)));
+    } else {
+        inlines.clear();
+    }
+}
+
+/// Read the content of a `para`, or of an inline element nested in it, up to
+/// its end element
+fn read_para(
+    parser: &mut EventReader<BufReader<File>>,
+    inlines: &mut Vec<Inline>,
+    blocks: &mut Vec<Block>,
+) -> Result<(), std::io::Error> {
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::Characters(ref chars)) | Ok(XmlEvent::CData(ref chars)) => {
+                inlines.push(Inline::Text(chars.clone()))
+            }
+            Ok(XmlEvent::StartElement {
+                ref name,
+                ref attributes,
+                ..
+            }) => match name.local_name.as_str() {
+                "ref" => inlines.push(Inline::Ref(read_reference(parser, attributes)?)),
+                "computeroutput" => inlines.push(Inline::Code(
+                    read_characters_only(parser)?.unwrap_or_default(),
+                )),
+                "programlisting" | "verbatim" => {
+                    flush(inlines, blocks);
+                    blocks.push(Block::Code(read_code(parser)?));
+                }
+                "itemizedlist" | "orderedlist" => {
+                    flush(inlines, blocks);
+                    blocks.push(read_list(parser, name.local_name == "orderedlist")?);
+                }
+                "simplesect" => {
+                    flush(inlines, blocks);
+                    let kind = read_xml_attribute(attributes, "kind").unwrap_or_default();
+                    blocks.push(Block::Section {
+                        kind,
+                        title: None,
+                        content: read_blocks(parser)?,
+                    });
+                }
+                "xrefsect" => {
+                    flush(inlines, blocks);
+                    blocks.push(read_xrefsect(parser, attributes)?);
+                }
+                "parameterlist" => {
+                    flush(inlines, blocks);
+                    blocks.push(read_parameters(parser, attributes)?);
+                }
+                "linebreak" => {
+                    inlines.push(Inline::Text(String::from("\n")));
+                    skip_element(parser)?;
+                }
+                _ => read_para(parser, inlines, blocks)?,
+            },
+            Ok(XmlEvent::EndElement { .. }) => break,
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn read_block_element(
+    parser: &mut EventReader<BufReader<File>>,
+    name: &str,
+    blocks: &mut Vec<Block>,
+) -> Result<(), std::io::Error> {
+    match name {
+        "para" => {
+            let mut inlines = Vec::new();
+            read_para(parser, &mut inlines, blocks)?;
+            flush(&mut inlines, blocks);
+        }
+        "sect1" | "sect2" | "sect3" | "sect4" | "internal" => {
+            blocks.extend(read_blocks(parser)?);
+        }
+        _ => skip_element(parser)?,
+    }
+    Ok(())
+}
+
+/// Read the paragraphs of the current element up to its end element
+pub fn read_blocks(
+    parser: &mut EventReader<BufReader<File>>,
+) -> Result<Vec<Block>, std::io::Error> {
+    let mut blocks = Vec::new();
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement { ref name, .. }) => {
+                read_block_element(parser, name.local_name.as_str(), &mut blocks)?
+            }
+            Ok(XmlEvent::EndElement { .. }) => break,
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(blocks)
+}
+
+/// Read a `briefdescription`, `detaileddescription` or `parameterdescription`
+pub fn read_description_blocks(
+    parser: &mut EventReader<BufReader<File>>,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<Block>, std::io::Error> {
+    let mut blocks = Vec::new();
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement { ref name, .. }) => {
+                read_block_element(parser, name.local_name.as_str(), &mut blocks)?
+            }
+            Ok(XmlEvent::EndElement { ref name }) => {
+                if name.local_name != "detaileddescription"
+                    && name.local_name != "briefdescription"
+                    && name.local_name != "parameterdescription"
+                {
+                    warnings.push(ParseWarning::new(format!(
+                        "Inconsistency {}, expected briefdescription or detailed description",
+                        name.local_name.as_str()
+                    )));
+                }
+                break;
+            }
+            Ok(XmlEvent::EndDocument) => return Err(unexpected_end()),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.clone(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(blocks)
+}
+
+/// `None` if the description has no text
+pub fn non_empty(blocks: Vec<Block>) -> Option<Description> {
+    let description = Description::from_blocks(blocks);
+    match description.is_empty() {
+        true => None,
+        false => Some(description),
+    }
+}
+
+pub fn read_description(
+    parser: &mut EventReader<BufReader<File>>,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Option<Description>, std::io::Error> {
+    Ok(non_empty(read_description_blocks(parser, warnings)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_targets() {
+        assert!(is_identifier_target("Widget"));
+        assert!(is_identifier_target("sdk::Widget::show()"));
+        assert!(is_identifier_target("Widget#show."));
+        assert!(is_identifier_target("::sdk::Widget::~Widget"));
+    }
+
+    #[test]
+    fn prose_and_urls_are_not_targets() {
+        assert!(!is_identifier_target("the user manual"));
+        assert!(!is_identifier_target("https://example.com/docs"));
+        assert!(!is_identifier_target(""));
+    }
+
+    #[test]
+    fn plain_see_section_is_not_a_reference() {
+        let see = |text: &str| Block::Section {
+            kind: String::from("see"),
+            title: None,
+            content: vec![Block::Paragraph(vec![Inline::Text(String::from(text))])],
+        };
+        let prose = Description::from_blocks(vec![see("https://example.com")]);
+        assert!(prose.references.is_empty());
+        let name = Description::from_blocks(vec![see("Widget::show")]);
+        assert_eq!(name.references.len(), 1);
+        assert_eq!(name.references[0].refid, None);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::common::*;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    ParseError,
+    ClassNameCase,
+    ClassDescription,
+    EnumNameCase,
+    EnumDescription,
+    EnumValueNameCase,
+    EnumValueDescription,
+    StaticConstAttributeName,
+    StaticAttributeName,
+    AttributeName,
+    AttributeDescription,
+    PublicAttribute,
+    FunctionDescription,
+    ReturnDescription,
+    UnknownParameter,
+    ParameterDescription,
+    ParameterName,
+    DuplicateClassName,
+    EnumValueClash,
+    UnresolvedReference,
+    SeePrivate,
+    SeeDeprecated,
+    HeaderName,
+    ClassesPerHeader,
+    InternalHeader,
+    PropertyDescription,
+    PropertyNotify,
+    SignalReturn,
+    DeprecatedDescription,
+    ParameterCount,
+    BoolParameterCount,
+    OverloadCount,
+    PublicMethodCount,
+    NestingDepth,
+    GroupMembership,
+    GroupTitle,
+    GroupDescription,
+    FileDescription,
+    FileLicense,
+    MacroNameCase,
+    MacroDescription,
+    MacroPrefix,
+    UnknownCommand,
+    DoxygenWarning,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 44] = [
+        Rule::ParseError,
+        Rule::ClassNameCase,
+        Rule::ClassDescription,
+        Rule::EnumNameCase,
+        Rule::EnumDescription,
+        Rule::EnumValueNameCase,
+        Rule::EnumValueDescription,
+        Rule::StaticConstAttributeName,
+        Rule::StaticAttributeName,
+        Rule::AttributeName,
+        Rule::AttributeDescription,
+        Rule::PublicAttribute,
+        Rule::FunctionDescription,
+        Rule::ReturnDescription,
+        Rule::UnknownParameter,
+        Rule::ParameterDescription,
+        Rule::ParameterName,
+        Rule::DuplicateClassName,
+        Rule::EnumValueClash,
+        Rule::UnresolvedReference,
+        Rule::SeePrivate,
+        Rule::SeeDeprecated,
+        Rule::HeaderName,
+        Rule::ClassesPerHeader,
+        Rule::InternalHeader,
+        Rule::PropertyDescription,
+        Rule::PropertyNotify,
+        Rule::SignalReturn,
+        Rule::DeprecatedDescription,
+        Rule::ParameterCount,
+        Rule::BoolParameterCount,
+        Rule::OverloadCount,
+        Rule::PublicMethodCount,
+        Rule::NestingDepth,
+        Rule::GroupMembership,
+        Rule::GroupTitle,
+        Rule::GroupDescription,
+        Rule::FileDescription,
+        Rule::FileLicense,
+        Rule::MacroNameCase,
+        Rule::MacroDescription,
+        Rule::MacroPrefix,
+        Rule::UnknownCommand,
+        Rule::DoxygenWarning,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::ParseError => "parse-error",
+            Rule::ClassNameCase => "class-name-case",
+            Rule::ClassDescription => "class-description",
+            Rule::EnumNameCase => "enum-name-case",
+            Rule::EnumDescription => "enum-description",
+            Rule::EnumValueNameCase => "enum-value-name-case",
+            Rule::EnumValueDescription => "enum-value-description",
+            Rule::StaticConstAttributeName => "static-const-attribute-name",
+            Rule::StaticAttributeName => "static-attribute-name",
+            Rule::AttributeName => "attribute-name",
+            Rule::AttributeDescription => "attribute-description",
+            Rule::PublicAttribute => "public-attribute",
+            Rule::FunctionDescription => "function-description",
+            Rule::ReturnDescription => "return-description",
+            Rule::UnknownParameter => "unknown-parameter",
+            Rule::ParameterDescription => "parameter-description",
+            Rule::ParameterName => "parameter-name",
+            Rule::DuplicateClassName => "duplicate-class-name",
+            Rule::EnumValueClash => "enum-value-clash",
+            Rule::UnresolvedReference => "unresolved-reference",
+            Rule::SeePrivate => "see-private",
+            Rule::SeeDeprecated => "see-deprecated",
+            Rule::HeaderName => "header-name",
+            Rule::ClassesPerHeader => "classes-per-header",
+            Rule::InternalHeader => "internal-header",
+            Rule::PropertyDescription => "property-description",
+            Rule::PropertyNotify => "property-notify",
+            Rule::SignalReturn => "signal-return",
+            Rule::DeprecatedDescription => "deprecated-description",
+            Rule::ParameterCount => "parameter-count",
+            Rule::BoolParameterCount => "bool-parameter-count",
+            Rule::OverloadCount => "overload-count",
+            Rule::PublicMethodCount => "public-method-count",
+            Rule::NestingDepth => "nesting-depth",
+            Rule::GroupMembership => "group-membership",
+            Rule::GroupTitle => "group-title",
+            Rule::GroupDescription => "group-description",
+            Rule::FileDescription => "file-description",
+            Rule::FileLicense => "file-license",
+            Rule::MacroNameCase => "macro-name-case",
+            Rule::MacroDescription => "macro-description",
+            Rule::MacroPrefix => "macro-prefix",
+            Rule::UnknownCommand => "unknown-command",
+            Rule::DoxygenWarning => "doxygen-warning",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::ParseError => "The doxygen XML could not be read consistently",
+            Rule::ClassNameCase => "Class names start with an upper case letter",
+            Rule::ClassDescription => "Classes have a description",
+            Rule::EnumNameCase => "Enum names start with an upper case letter",
+            Rule::EnumDescription => "Enums have a description",
+            Rule::EnumValueNameCase => "Enum values start with an upper case letter",
+            Rule::EnumValueDescription => "Enum values have a description",
+            Rule::StaticConstAttributeName => {
+                "Static const attributes start with an upper case letter"
+            }
+            Rule::StaticAttributeName => "Static attributes start with s_",
+            Rule::AttributeName => "Class attributes start with m_",
+            Rule::AttributeDescription => "Attributes have a description",
+            Rule::PublicAttribute => "Class attributes are not public",
+            Rule::FunctionDescription => "Functions have a description",
+            Rule::ReturnDescription => "Returned values have a description",
+            Rule::UnknownParameter => "Documented parameters exist",
+            Rule::ParameterDescription => "Parameters have a description",
+            Rule::ParameterName => "Parameters start with p_",
+            Rule::DuplicateClassName => "Classes of different namespaces have different names",
+            Rule::EnumValueClash => "Values of unscoped enums of a namespace have different names",
+            Rule::UnresolvedReference => "References and copied documentations have a known target",
+            Rule::SeePrivate => "See also sections do not point to private members",
+            Rule::SeeDeprecated => "See also sections do not point to deprecated entities",
+            Rule::HeaderName => "Classes are declared in a header named after them",
+            Rule::ClassesPerHeader => "Headers do not declare too many classes",
+            Rule::InternalHeader => {
+                "Public types are not declared under detail or internal folders"
+            }
+            Rule::PropertyDescription => "Qt properties have a description",
+            Rule::PropertyNotify => "Qt properties that are not constant name their NOTIFY signal",
+            Rule::SignalReturn => "Qt signals do not document a returned value",
+            Rule::DeprecatedDescription => {
+                "Deprecated entities have a deprecated section naming their replacement"
+            }
+            Rule::ParameterCount => "Functions do not have too many parameters",
+            Rule::BoolParameterCount => "Functions do not have too many boolean parameters",
+            Rule::OverloadCount => "Functions do not have too many overloads",
+            Rule::PublicMethodCount => "Classes do not have too many public methods",
+            Rule::NestingDepth => "Inner classes are not nested too deeply",
+            Rule::GroupMembership => {
+                "Public classes of the grouped namespaces belong to exactly one group"
+            }
+            Rule::GroupTitle => "Groups have a title",
+            Rule::GroupDescription => "Groups have a description",
+            Rule::FileDescription => "Headers have a @file block with a brief description",
+            Rule::FileLicense => "Headers start with the license header",
+            Rule::MacroNameCase => "Macro names are in upper snake case",
+            Rule::MacroDescription => "Macros have a description",
+            Rule::MacroPrefix => "Macro names start with the project prefix",
+            Rule::UnknownCommand => "Documentation comments only use known doxygen commands",
+            Rule::DoxygenWarning => "Doxygen does not report other warnings",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Rule> {
+        Rule::ALL.iter().find(|rule| rule.id() == id).copied()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Off,
+    Note,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(Severity::Off),
+            "note" => Some(Severity::Note),
+            "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Off => "off",
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+    /// Author of the line, from git blame
+    pub author: Option<String>,
+    /// Owners of the file, from CODEOWNERS
+    pub owners: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(rule: Rule, message: String, location: &Option<Location>) -> Diagnostic {
+        Diagnostic {
+            rule,
+            severity: Severity::Warning,
+            message,
+            location: location.clone(),
+            author: None,
+            owners: Vec::new(),
+        }
+    }
+}
+
+impl From<ParseWarning> for Diagnostic {
+    fn from(warning: ParseWarning) -> Diagnostic {
+        Diagnostic {
+            rule: Rule::ParseError,
+            severity: Severity::Warning,
+            message: warning.message,
+            location: warning.location,
+            author: None,
+            owners: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use crate::attribute::*;
+use crate::class::*;
+use crate::common::*;
+use crate::enumerated::*;
+use crate::function::*;
+use crate::project::*;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiChange {
+    pub kind: ChangeKind,
+    pub entity: &'static str,
+    pub name: String,
+    pub details: Option<String>,
+    pub breaking: bool,
+}
+
+impl ApiChange {
+    fn added(entity: &'static str, name: String) -> ApiChange {
+        ApiChange {
+            kind: ChangeKind::Added,
+            entity,
+            name,
+            details: None,
+            breaking: false,
+        }
+    }
+
+    fn removed(entity: &'static str, name: String) -> ApiChange {
+        ApiChange {
+            kind: ChangeKind::Removed,
+            entity,
+            name,
+            details: None,
+            breaking: true,
+        }
+    }
+
+    fn changed(entity: &'static str, name: String, details: String) -> ApiChange {
+        ApiChange {
+            kind: ChangeKind::Changed,
+            entity,
+            name,
+            details: Some(details),
+            breaking: true,
+        }
+    }
+}
+
+impl fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.breaking {
+            write!(f, "[BREAKING] ")?;
+        }
+        let kind = match self.kind {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Changed => "Changed",
+        };
+        write!(f, "{} {} {}", kind, self.entity, self.name)?;
+        if let Some(details) = &self.details {
+            write!(f, ": {}", details)?;
+        }
+        Ok(())
+    }
+}
+
+fn signature(class: &Class, function: &Function) -> String {
+    let parameters: Vec<&str> = function
+        .parameters
+        .iter()
+        .filter_map(|p| p.ctype.as_deref())
+        .collect();
+    format!(
+        "{}::{}({}){}",
+        class.name,
+        function.name,
+        parameters.join(", "),
+        match function.is_const {
+            true => " const",
+            false => "",
+        }
+    )
+}
+
+fn public_functions(class: &Class) -> BTreeMap<String, &Function> {
+    class
+        .functions
+        .iter()
+        .filter(|f| f.access == Access::Public)
+        .map(|f| (signature(class, f), f))
+        .collect()
+}
+
+fn public_attributes(class: &Class) -> BTreeMap<String, &Attribute> {
+    class
+        .attributes
+        .iter()
+        .filter(|a| a.access == Access::Public)
+        .map(|a| (format!("{}::{}", class.name, a.name), a))
+        .collect()
+}
+
+fn diff_functions(old: &Class, new: &Class, changes: &mut Vec<ApiChange>) {
+    let old_functions = public_functions(old);
+    let new_functions = public_functions(new);
+
+    let mut removed: Vec<(&String, &Function)> = Vec::new();
+    let mut added: Vec<(&String, &Function)> = Vec::new();
+    for (name, function) in &old_functions {
+        match new_functions.get(name) {
+            Some(other) => {
+                if function.ret_type != other.ret_type {
+                    changes.push(ApiChange::changed(
+                        "method",
+                        name.clone(),
+                        format!(
+                            "return type {} -> {}",
+                            function.ret_type.as_deref().unwrap_or_default(),
+                            other.ret_type.as_deref().unwrap_or_default()
+                        ),
+                    ));
+                }
+                if function.is_static != other.is_static {
+                    changes.push(ApiChange::changed(
+                        "method",
+                        name.clone(),
+                        String::from(match other.is_static {
+                            true => "now static",
+                            false => "no more static",
+                        }),
+                    ));
+                }
+            }
+            None => removed.push((name, function)),
+        }
+    }
+    for (name, function) in &new_functions {
+        if !old_functions.contains_key(name) {
+            added.push((name, function));
+        }
+    }
+
+    // A single overload replaced by another one is reported as a signature change
+    for (name, function) in removed {
+        let old_count = old
+            .functions
+            .iter()
+            .filter(|f| f.name == function.name)
+            .count();
+        let new_count = new
+            .functions
+            .iter()
+            .filter(|f| f.name == function.name)
+            .count();
+        match added.iter().position(|(_, f)| f.name == function.name) {
+            Some(index) if old_count == 1 && new_count == 1 => {
+                let (new_name, _) = added.remove(index);
+                changes.push(ApiChange::changed(
+                    "method",
+                    name.clone(),
+                    format!("signature is now {}", new_name),
+                ));
+            }
+            _ => changes.push(ApiChange::removed("method", name.clone())),
+        }
+    }
+    for (name, _) in added {
+        changes.push(ApiChange::added("method", name.clone()));
+    }
+}
+
+fn diff_attributes(old: &Class, new: &Class, changes: &mut Vec<ApiChange>) {
+    let old_attributes = public_attributes(old);
+    let new_attributes = public_attributes(new);
+
+    for (name, attribute) in &old_attributes {
+        match new_attributes.get(name) {
+            Some(other) => {
+                if attribute.ctype != other.ctype {
+                    changes.push(ApiChange::changed(
+                        "attribute",
+                        name.clone(),
+                        format!("type {} -> {}", attribute.ctype, other.ctype),
+                    ));
+                }
+                if attribute.is_static != other.is_static {
+                    changes.push(ApiChange::changed(
+                        "attribute",
+                        name.clone(),
+                        String::from(match other.is_static {
+                            true => "now static",
+                            false => "no more static",
+                        }),
+                    ));
+                }
+            }
+            None => changes.push(ApiChange::removed("attribute", name.clone())),
+        }
+    }
+    for name in new_attributes.keys() {
+        if !old_attributes.contains_key(name) {
+            changes.push(ApiChange::added("attribute", name.clone()));
+        }
+    }
+}
+
+fn diff_enums<'a, I>(old: I, new: I, changes: &mut Vec<ApiChange>)
+where
+    I: Iterator<Item = &'a Enumerated>,
+{
+    let old_enums: BTreeMap<&String, &Enumerated> = old.map(|e| (&e.full_name, e)).collect();
+    let new_enums: BTreeMap<&String, &Enumerated> = new.map(|e| (&e.full_name, e)).collect();
+
+    for (name, enumerated) in &old_enums {
+        match new_enums.get(name) {
+            Some(other) => {
+                for value in &enumerated.values {
+                    if !other.values.iter().any(|v| v.name == value.name) {
+                        changes.push(ApiChange::removed(
+                            "enum value",
+                            format!("{}::{}", name, value.name),
+                        ));
+                    }
+                }
+                for value in &other.values {
+                    if !enumerated.values.iter().any(|v| v.name == value.name) {
+                        changes.push(ApiChange::added(
+                            "enum value",
+                            format!("{}::{}", name, value.name),
+                        ));
+                    }
+                }
+            }
+            None => changes.push(ApiChange::removed("enum", (*name).clone())),
+        }
+    }
+    for name in new_enums.keys() {
+        if !old_enums.contains_key(name) {
+            changes.push(ApiChange::added("enum", (*name).clone()));
+        }
+    }
+}
+
+fn classes(project: &Project) -> BTreeMap<&String, &Class> {
+    project
+        .classes
+        .iter()
+        .chain(project.objects.iter())
+        .map(|c| (&c.name, c))
+        .collect()
+}
+
+/// Compare the public API of two projects
+pub fn diff_projects(old: &Project, new: &Project) -> Vec<ApiChange> {
+    let mut changes: Vec<ApiChange> = Vec::new();
+
+    let old_classes = classes(old);
+    let new_classes = classes(new);
+
+    for (name, class) in &old_classes {
+        match new_classes.get(name) {
+            Some(other) => {
+                if class.is_struct != other.is_struct {
+                    changes.push(ApiChange::changed(
+                        class.object_type(),
+                        (*name).clone(),
+                        format!("now a {}", other.object_type()),
+                    ));
+                }
+                diff_functions(class, other, &mut changes);
+                diff_attributes(class, other, &mut changes);
+                diff_enums(class.enums.iter(), other.enums.iter(), &mut changes);
+            }
+            None => changes.push(ApiChange::removed(class.object_type(), (*name).clone())),
+        }
+    }
+    for (name, class) in &new_classes {
+        if !old_classes.contains_key(name) {
+            changes.push(ApiChange::added(class.object_type(), (*name).clone()));
+        }
+    }
+
+    diff_enums(old.enums.iter(), new.enums.iter(), &mut changes);
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(name: &str, parameters: &[&str], ret_type: &str) -> Function {
+        let mut function = Function::new();
+        function.name = String::from(name);
+        function.access = Access::Public;
+        function.ret_type = Some(String::from(ret_type));
+        for ctype in parameters {
+            let mut parameter = Parameter::new();
+            parameter.ctype = Some(String::from(*ctype));
+            function.parameters.push(parameter);
+        }
+        function
+    }
+
+    fn project(functions: Vec<Function>, values: &[&str]) -> Project {
+        let mut enumerated = Enumerated::new();
+        enumerated.name = String::from("Mode");
+        enumerated.full_name = String::from("sdk::Widget::Mode");
+        for value in values {
+            enumerated.values.push(EnumValue {
+                id: String::new(),
+                name: String::from(*value),
+                brief: None,
+            });
+        }
+        let mut class = Class::new();
+        class.name = String::from("sdk::Widget");
+        class.functions = functions;
+        class.enums.push(enumerated);
+        let mut project = Project::new();
+        project.classes.push_back(class);
+        project
+    }
+
+    fn summary(changes: &[ApiChange]) -> Vec<String> {
+        changes.iter().map(|change| change.to_string()).collect()
+    }
+
+    #[test]
+    fn identical_projects_have_no_change() {
+        let old = project(vec![method("size", &[], "int")], &["A"]);
+        let new = project(vec![method("size", &[], "int")], &["A"]);
+        assert!(diff_projects(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn added_method_is_not_breaking() {
+        let old = project(vec![], &[]);
+        let new = project(vec![method("clear", &[], "void")], &[]);
+        assert_eq!(
+            summary(&diff_projects(&old, &new)),
+            ["Added method sdk::Widget::clear()"]
+        );
+    }
+
+    #[test]
+    fn removed_method_is_breaking() {
+        let old = project(vec![method("clear", &[], "void")], &[]);
+        let new = project(vec![], &[]);
+        assert_eq!(
+            summary(&diff_projects(&old, &new)),
+            ["[BREAKING] Removed method sdk::Widget::clear()"]
+        );
+    }
+
+    #[test]
+    fn changed_return_type_is_breaking() {
+        let old = project(vec![method("size", &[], "int")], &[]);
+        let new = project(vec![method("size", &[], "size_t")], &[]);
+        assert_eq!(
+            summary(&diff_projects(&old, &new)),
+            ["[BREAKING] Changed method sdk::Widget::size(): return type int -> size_t"]
+        );
+    }
+
+    #[test]
+    fn replaced_overload_is_a_signature_change() {
+        let old = project(vec![method("resize", &["int"], "void")], &[]);
+        let new = project(vec![method("resize", &["int", "int"], "void")], &[]);
+        assert_eq!(
+            summary(&diff_projects(&old, &new)),
+            ["[BREAKING] Changed method sdk::Widget::resize(int): signature is now sdk::Widget::resize(int, int)"]
+        );
+    }
+
+    #[test]
+    fn const_qualifier_change_is_detected() {
+        let old = project(vec![method("size", &[], "int")], &[]);
+        let mut size = method("size", &[], "int");
+        size.is_const = true;
+        let new = project(vec![size], &[]);
+        assert_eq!(
+            summary(&diff_projects(&old, &new)),
+            ["[BREAKING] Changed method sdk::Widget::size(): signature is now sdk::Widget::size() const"]
+        );
+    }
+
+    #[test]
+    fn enum_values_added_and_removed() {
+        let old = project(vec![], &["A", "B"]);
+        let new = project(vec![], &["A", "C"]);
+        assert_eq!(
+            summary(&diff_projects(&old, &new)),
+            [
+                "[BREAKING] Removed enum value sdk::Widget::Mode::B",
+                "Added enum value sdk::Widget::Mode::C"
+            ]
+        );
+    }
+}
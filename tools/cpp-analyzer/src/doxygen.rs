@@ -1,14 +1,32 @@
+use crate::common::*;
+use crate::diagnostic::*;
+use crate::project::LoadOptions;
+
 use chrono::Utc;
+use regex::Regex;
 use std::env;
 use std::fs::OpenOptions;
 use std::fs::{self};
+use std::io::Error;
 use std::io::Write;
-use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str::FromStr;
 
+pub const DEFAULT_DOXYGEN: &str = "C:/Program Files/doxygen/bin/doxygen.exe";
+
+/// Doxyfile value, quoted when it contains spaces or quotes
+fn quote(value: &str) -> String {
+    if value.contains(' ') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\\\""))
+    } else {
+        String::from(value)
+    }
+}
+
 pub fn generate_doxyfile(
-    input: &Vec<String>,
+    input: &[String],
+    options: &LoadOptions,
     output_folder: Option<&str>,
 ) -> Result<(String, String), std::io::Error> {
     let now = Utc::now();
@@ -24,44 +42,166 @@ pub fn generate_doxyfile(
     };
 
     let temp_file = temp_directory.join("doxyfile");
-    println!("Generating Doxyfile {:?}", temp_file);
 
     //let mut file = File::open(&temp_file).unwrap();
     let mut file = OpenOptions::new()
         .write(true)
         .create(true)
+        .truncate(true)
         .open(&temp_file)?;
     write!(&mut file, "{}", INPUT).unwrap();
     for val in input.iter() {
-        write!(&mut file, "{} ", val.as_str()).unwrap();
+        write!(&mut file, "{} ", quote(val)).unwrap();
     }
-    write!(&mut file, "\n").unwrap();
-    write!(&mut file, "{}{}\n", OUTPUT, output).unwrap();
+    writeln!(&mut file).unwrap();
+    writeln!(&mut file, "{}{}", OUTPUT, output).unwrap();
     write!(&mut file, "{}", DOXYFILE).unwrap();
+    writeln!(
+        &mut file,
+        "{}{}",
+        WARN_LOGFILE,
+        quote(&warning_log(temp_file.to_str().unwrap()).to_string_lossy())
+    )
+    .unwrap();
+    if !options.exclude.is_empty() {
+        writeln!(
+            &mut file,
+            "{}{}",
+            EXCLUDE_PATTERNS,
+            options.exclude.join(" ")
+        )
+        .unwrap();
+    }
+    if !options.include_paths.is_empty() {
+        let paths: Vec<String> = options.include_paths.iter().map(|p| quote(p)).collect();
+        writeln!(&mut file, "{}{}", INCLUDE_PATH, paths.join(" ")).unwrap();
+    }
+    if !options.defines.is_empty() {
+        let defines: Vec<String> = options.defines.iter().map(|d| quote(d)).collect();
+        writeln!(&mut file, "{}YES", MACRO_EXPANSION).unwrap();
+        writeln!(&mut file, "{}{}", PREDEFINED, defines.join(" ")).unwrap();
+    }
 
-    return Ok((
+    Ok((
         String::from_str(temp_file.to_str().unwrap()).unwrap(),
         output,
-    ));
+    ))
+}
+
+/// File receiving the warnings of the doxygen run of `doxyfile`
+pub fn warning_log(doxyfile: &str) -> PathBuf {
+    match Path::new(doxyfile).parent() {
+        Some(folder) => folder.join("warnings.log"),
+        None => PathBuf::from("warnings.log"),
+    }
+}
+
+/// Rule matching the text of a doxygen warning
+fn classify_warning(text: &str) -> Rule {
+    if text.contains("of command @param is not found") {
+        Rule::UnknownParameter
+    } else if text.starts_with("The following parameter") {
+        Rule::ParameterDescription
+    } else if text.starts_with("return type of member") {
+        Rule::ReturnDescription
+    } else if text.starts_with("Found unknown command") {
+        Rule::UnknownCommand
+    } else if text.starts_with("unable to resolve reference") {
+        Rule::UnresolvedReference
+    } else if text.starts_with("Compound ") && text.contains("is not documented") {
+        Rule::ClassDescription
+    } else if text.starts_with("Member ") && text.contains("is not documented") {
+        if text.contains("(function)") || text.contains("(signal)") || text.contains("(slot)") {
+            Rule::FunctionDescription
+        } else if text.contains("(variable)") {
+            Rule::AttributeDescription
+        } else if text.contains("(enumeration)") {
+            Rule::EnumDescription
+        } else if text.contains("(enumvalue)") {
+            Rule::EnumValueDescription
+        } else if text.contains("(macro definition)") || text.contains("(define)") {
+            Rule::MacroDescription
+        } else if text.contains("(property)") {
+            Rule::PropertyDescription
+        } else {
+            Rule::DoxygenWarning
+        }
+    } else {
+        Rule::DoxygenWarning
+    }
+}
+
+/// Diagnostics of the warnings written with `WARN_FORMAT`, the lines that do
+/// not start a warning continue the previous one
+pub fn read_warnings(log: &Path) -> Result<Vec<Diagnostic>, std::io::Error> {
+    let content = match fs::read(log) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let format = Regex::new(r"^\[WARNING\] (.*):(-?\d+): (.*)$").unwrap();
+
+    let mut warnings: Vec<(Option<Location>, String)> = Vec::new();
+    for line in content.lines() {
+        if let Some(captures) = format.captures(line) {
+            let file = captures.get(1).map_or("", |m| m.as_str());
+            let line = captures[2].parse::<i32>().unwrap_or(0);
+            let location = match file {
+                "" | "<unknown>" => None,
+                _ => Some(Location {
+                    file: String::from(file),
+                    line,
+                }),
+            };
+            warnings.push((location, String::from(captures[3].trim())));
+        } else if let Some(text) = line.strip_prefix("[WARNING] ") {
+            warnings.push((None, String::from(text.trim())));
+        } else if let Some((_, text)) = warnings.last_mut() {
+            if !line.trim().is_empty() {
+                text.push(' ');
+                text.push_str(line.trim());
+            }
+        }
+    }
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for (location, text) in warnings {
+        let diagnostic = Diagnostic::new(
+            classify_warning(&text),
+            format!("doxygen: {}", text),
+            &location,
+        );
+        if !diagnostics
+            .iter()
+            .any(|d| d.message == diagnostic.message && d.location == diagnostic.location)
+        {
+            diagnostics.push(diagnostic);
+        }
+    }
+    Ok(diagnostics)
 }
 
-// "C:/Program Files/doxygen/bin/doxygen.exe"
 pub fn launch_doxygen(doxyfile: &str, doxygen_binary: &str) -> Result<(), std::io::Error> {
     match Command::new(doxygen_binary).args([doxyfile]).output() {
         Ok(value) => match value.status.code().unwrap_or(127) {
             0 => Ok(()),
-            value => Err(Error::new(
-                ErrorKind::Other,
-                format!("Doxygen exit with error status {}", value),
-            )),
+            value => Err(Error::other(format!(
+                "Doxygen exit with error status {}",
+                value
+            ))),
         },
         Err(error) => Err(error),
     }
 }
 
-const OUTPUT: &'static str = "OUTPUT_DIRECTORY       = ";
-const INPUT: &'static str = "INPUT                  = ";
-const DOXYFILE: &'static str = "DOXYFILE_ENCODING      = UTF-8
+const OUTPUT: &str = "OUTPUT_DIRECTORY       = ";
+const INPUT: &str = "INPUT                  = ";
+const EXCLUDE_PATTERNS: &str = "EXCLUDE_PATTERNS       = ";
+const WARN_LOGFILE: &str = "WARN_LOGFILE           = ";
+const INCLUDE_PATH: &str = "INCLUDE_PATH           = ";
+const MACRO_EXPANSION: &str = "MACRO_EXPANSION        = ";
+const PREDEFINED: &str = "PREDEFINED             = ";
+const DOXYFILE: &str = "DOXYFILE_ENCODING      = UTF-8
 PROJECT_NAME           = \"My Project\"
 PROJECT_NUMBER         =
 PROJECT_BRIEF          =
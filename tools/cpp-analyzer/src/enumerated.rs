@@ -1,39 +1,63 @@
 use crate::common::*;
+use crate::description::*;
 
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
+use xml::attribute::OwnedAttribute;
 use xml::reader::{EventReader, XmlEvent};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Enumerated {
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub full_name: String,
-    pub brief: Option<String>,
-    pub detailed: Option<String>,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
     pub values: Vec<EnumValue>,
     pub location: Option<Location>,
+    #[serde(default)]
+    pub is_strong: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumValue {
+    #[serde(default)]
+    pub id: String,
     pub name: String,
-    pub brief: Option<String>,
+    pub brief: Option<Description>,
+}
+
+impl Default for Enumerated {
+    fn default() -> Self {
+        Enumerated::new()
+    }
 }
 
 impl Enumerated {
-    fn new() -> Enumerated {
+    pub fn new() -> Enumerated {
         Enumerated {
+            id: String::new(),
             name: String::new(),
             full_name: String::new(),
             brief: Option::None,
             detailed: Option::None,
             values: Vec::new(),
             location: Option::None,
+            is_strong: false,
         }
     }
 
-    pub fn read(parser: &mut EventReader<BufReader<File>>) -> Result<Enumerated, std::io::Error> {
+    pub fn read(
+        parser: &mut EventReader<BufReader<File>>,
+        xml_attributes: &[OwnedAttribute],
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Enumerated, std::io::Error> {
         let mut enum_obj = Enumerated::new();
+        enum_obj.id = read_xml_attribute(xml_attributes, "id").unwrap_or_default();
+        enum_obj.is_strong =
+            read_xml_attribute(xml_attributes, "strong").unwrap_or_default() == "yes";
         let mut depth = 0;
         loop {
             match parser.next() {
@@ -53,13 +77,20 @@ impl Enumerated {
                             "name" => {
                                 enum_obj.name = read_characters_only(parser)?.unwrap_or_default()
                             }
-                            "briefdescription" => enum_obj.brief = read_description(parser)?,
+                            "briefdescription" => {
+                                enum_obj.brief = read_description(parser, warnings)?
+                            }
+                            "detaileddescription" => {
+                                enum_obj.detailed = read_description(parser, warnings)?
+                            }
                             "location" => {
                                 enum_obj.location = Some(Location::read(attributes));
                                 depth += 1;
                             }
                             "enumvalue" => {
-                                enum_obj.values.push(Enumerated::read_enum_value(parser)?);
+                                enum_obj.values.push(Enumerated::read_enum_value(
+                                    parser, attributes, warnings,
+                                )?);
                             }
                             _ => depth += 1,
                         }
@@ -68,10 +99,10 @@ impl Enumerated {
                 Ok(XmlEvent::EndElement { ref name }) => {
                     if depth == 0 {
                         if name.local_name != "memberdef" {
-                            println!(
+                            warnings.push(ParseWarning::new(format!(
                                 "Inconsistency {}, expected memberdef",
                                 name.local_name.as_str()
-                            );
+                            )));
                         }
                         break;
                     }
@@ -94,8 +125,11 @@ impl Enumerated {
 
     pub fn read_enum_value(
         parser: &mut EventReader<BufReader<File>>,
+        xml_attributes: &[OwnedAttribute],
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<EnumValue, std::io::Error> {
         let mut value = EnumValue {
+            id: read_xml_attribute(xml_attributes, "id").unwrap_or_default(),
             name: String::new(),
             brief: Option::None,
         };
@@ -111,7 +145,7 @@ impl Enumerated {
                             "name" => {
                                 value.name = read_characters_only(parser)?.unwrap_or_default()
                             }
-                            "briefdescription" => value.brief = read_description(parser)?,
+                            "briefdescription" => value.brief = read_description(parser, warnings)?,
                             _ => depth += 1,
                         }
                     }
@@ -119,10 +153,10 @@ impl Enumerated {
                 Ok(XmlEvent::EndElement { ref name }) => {
                     if depth == 0 {
                         if name.local_name != "enumvalue" {
-                            println!(
+                            warnings.push(ParseWarning::new(format!(
                                 "Inconsistency {}, expected enumvalue",
                                 name.local_name.as_str()
-                            );
+                            )));
                         }
                         break;
                     }
@@ -141,4 +175,42 @@ impl Enumerated {
         }
         Ok(value)
     }
+
+    /// Read the enums declared at namespace or file scope in a compound
+    pub fn read_compound(
+        filename: &str,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Vec<Enumerated>, std::io::Error> {
+        let file = std::io::BufReader::new(File::open(filename)?);
+        let mut parser = EventReader::new(file);
+
+        let mut enums: Vec<Enumerated> = Vec::new();
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) if name.local_name == "memberdef"
+                    && read_xml_attribute(attributes, "kind").unwrap_or_default() == "enum" =>
+                {
+                    let first = warnings.len();
+                    let enumerated = Enumerated::read(&mut parser, attributes, warnings)?;
+                    let entity = format!("enum {}", enumerated.full_name);
+                    if !skip_on_warnings(warnings, first, &entity, &enumerated.location) {
+                        enums.push(enumerated);
+                    }
+                }
+                Ok(XmlEvent::EndDocument) => break,
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(enums)
+    }
 }
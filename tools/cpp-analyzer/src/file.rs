@@ -0,0 +1,198 @@
+use crate::common::*;
+use crate::description::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use xml::{
+    attribute::OwnedAttribute,
+    reader::{EventReader, XmlEvent},
+};
+
+/// Preprocessor macro declared with `#define`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Macro {
+    pub id: String,
+    pub name: String,
+    /// Parameters of a function-like macro
+    pub parameters: Option<Vec<String>>,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
+    pub location: Option<Location>,
+}
+
+/// Header or source file compound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceFile {
+    pub id: String,
+    pub name: String,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
+    pub macros: Vec<Macro>,
+    pub location: Option<Location>,
+}
+
+impl Default for Macro {
+    fn default() -> Self {
+        Macro::new()
+    }
+}
+
+impl Macro {
+    pub fn new() -> Macro {
+        Macro {
+            id: String::new(),
+            name: String::new(),
+            parameters: Option::None,
+            brief: Option::None,
+            detailed: Option::None,
+            location: Option::None,
+        }
+    }
+
+    pub fn read(
+        parser: &mut EventReader<BufReader<File>>,
+        xml_attributes: &[OwnedAttribute],
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Macro, std::io::Error> {
+        let mut define = Macro::new();
+        define.id = read_xml_attribute(xml_attributes, "id").unwrap_or_default();
+
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) => match name.local_name.as_str() {
+                    "name" => define.name = read_characters_only(parser)?.unwrap_or_default(),
+                    "param" => {
+                        let parameter = read_characters_only(parser)?.unwrap_or_default();
+                        define
+                            .parameters
+                            .get_or_insert_with(Vec::new)
+                            .push(String::from(parameter.trim()));
+                    }
+                    "briefdescription" => define.brief = read_description(parser, warnings)?,
+                    "detaileddescription" => define.detailed = read_description(parser, warnings)?,
+                    "location" => define.location = Some(Location::read(attributes)),
+                    "initializer" => skip_element(parser)?,
+                    _ => {}
+                },
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "memberdef" => {
+                    break;
+                }
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(define)
+    }
+}
+
+impl Default for SourceFile {
+    fn default() -> Self {
+        SourceFile::new()
+    }
+}
+
+impl SourceFile {
+    pub fn new() -> SourceFile {
+        SourceFile {
+            id: String::new(),
+            name: String::new(),
+            brief: Option::None,
+            detailed: Option::None,
+            macros: Vec::new(),
+            location: Option::None,
+        }
+    }
+
+    fn read_content(
+        &mut self,
+        parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<(), std::io::Error> {
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) => match name.local_name.as_str() {
+                    "compoundname" => self.name = read_characters_only(parser)?.unwrap_or_default(),
+                    "memberdef"
+                        if read_xml_attribute(attributes, "kind").unwrap_or_default()
+                            == "define" =>
+                    {
+                        let first = warnings.len();
+                        let define = Macro::read(parser, attributes, warnings)?;
+                        let entity = format!("macro {}", define.name);
+                        if !skip_on_warnings(warnings, first, &entity, &define.location) {
+                            self.macros.push(define);
+                        }
+                    }
+                    "memberdef" => skip_element(parser)?,
+                    "briefdescription" => self.brief = read_description(parser, warnings)?,
+                    "detaileddescription" => self.detailed = read_description(parser, warnings)?,
+                    "location" => {
+                        let mut location = Location::read(attributes);
+                        // File locations have no line
+                        location.line = location.line.max(1);
+                        self.location = Some(location);
+                    }
+                    _ => {}
+                },
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "compounddef" => {
+                    break;
+                }
+                Ok(XmlEvent::EndDocument) => break,
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(
+        filename: &str,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<SourceFile, std::io::Error> {
+        let file = std::io::BufReader::new(File::open(filename)?);
+        let mut parser = EventReader::new(file);
+
+        let mut source = SourceFile::new();
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) if name.local_name == "compounddef" => {
+                    source.id = read_xml_attribute(attributes, "id").unwrap_or_default();
+                    source.read_content(&mut parser, warnings)?;
+                }
+                Ok(XmlEvent::EndDocument) => break,
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(source)
+    }
+}
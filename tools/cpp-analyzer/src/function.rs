@@ -1,87 +1,67 @@
 use crate::common::*;
+use crate::description::*;
 
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
 use xml::attribute::OwnedAttribute;
 use xml::reader::{EventReader, XmlEvent};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum FunctionKind {
+    #[default]
+    Method,
+    Signal,
+    Slot,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Function {
+    #[serde(default)]
+    pub id: String,
     pub access: Access,
     pub name: String,
     pub is_static: bool,
+    #[serde(default)]
+    pub is_const: bool,
     pub ret_type: Option<String>,
-    pub ret_description: Option<String>,
-    pub brief: Option<String>,
-    pub detailed: Option<String>,
+    pub ret_description: Option<Description>,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
     pub parameters: Vec<Parameter>,
     pub location: Option<Location>,
+    #[serde(default)]
+    pub kind: FunctionKind,
+}
+
+impl Default for Function {
+    fn default() -> Self {
+        Function::new()
+    }
 }
 
 impl Function {
     pub fn new() -> Function {
         Function {
+            id: String::new(),
             access: Access::Unknown,
             name: String::new(),
             is_static: false,
+            is_const: false,
             ret_type: Option::None,
             ret_description: Option::None,
             brief: Option::None,
             detailed: Option::None,
             parameters: Vec::new(),
             location: Option::None,
+            kind: FunctionKind::Method,
         }
     }
 
-    pub fn read_parameter_item(
-        &mut self,
-        parser: &mut EventReader<BufReader<File>>,
-    ) -> Result<(), std::io::Error> {
-        let mut param_name: Option<String> = Option::None;
-        let mut direction: Option<Direction> = Option::None;
-        let mut description: Option<String> = Option::None;
-        let mut depth = 0;
-        loop {
-            match parser.next() {
-                Ok(XmlEvent::StartElement {
-                    ref name,
-                    ref attributes,
-                    ..
-                }) => match name.local_name.as_str() {
-                    "parametername" => {
-                        direction = match read_xml_attribute(attributes, "direction") {
-                            Some(val) => direction_from_str(val.as_str()),
-                            None => Option::None,
-                        };
-                        param_name = read_characters_only(parser)?;
-                    }
-                    "parameterdescription" => description = read_description(parser)?,
-                    _ => depth += 1,
-                },
-                Ok(XmlEvent::EndElement { ref name }) => {
-                    if depth == 0 {
-                        if name.local_name != "parameteritem" {
-                            println!(
-                                "Inconsistency {}, expected parameteritem",
-                                name.local_name.as_str()
-                            );
-                        }
-                        break;
-                    }
-                    depth -= 1;
-                }
-                Err(e) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        e.clone(),
-                    ))
-                }
-                _ => {}
-            }
-        }
-
-        if param_name.is_some() {
-            let mut param = match self.parameters.iter_mut().find(|r| r.name == param_name) {
+    fn add_parameter_description(&mut self, item: &ParameterItem) {
+        for param_name in &item.names {
+            let param_name = Some(param_name.clone());
+            let param = match self.parameters.iter_mut().find(|r| r.name == param_name) {
                 Some(val) => val,
                 None => {
                     let mut p = Parameter::new();
@@ -90,87 +70,44 @@ impl Function {
                     self.parameters.last_mut().unwrap()
                 }
             };
-            param.description = description;
-            param.direction = direction;
+            param.description = non_empty(item.description.clone());
+            param.direction = item.direction;
         }
-
-        Ok(())
     }
 
-    pub fn read_return(
+    /// Read the detailed description, the parameters and the returned value
+    /// are documented apart
+    pub fn read_detailed_description(
         &mut self,
         parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<(), std::io::Error> {
-        let mut depth = 0;
-        loop {
-            match parser.next() {
-                Ok(XmlEvent::StartElement { ref name, .. }) => match name.local_name.as_str() {
-                    "para" => self.ret_description = read_characters_only(parser)?,
-                    _ => depth += 1,
-                },
-                Ok(XmlEvent::EndElement { ref name }) => {
-                    if depth == 0 {
-                        if name.local_name != "simplesect" {
-                            println!(
-                                "Inconsistency {}, expected simplesect",
-                                name.local_name.as_str()
-                            );
-                        }
-                        break;
-                    }
-                    depth -= 1;
-                }
-                Err(e) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        e.clone(),
-                    ))
-                }
-                _ => {}
-            }
-        }
+        let blocks = read_description_blocks(parser, warnings)?;
+        self.set_detailed_description(blocks);
         Ok(())
     }
 
-    pub fn read_detailed_description(
-        &mut self,
-        parser: &mut EventReader<BufReader<File>>,
-    ) -> Result<(), std::io::Error> {
-        let mut depth = 0;
-        loop {
-            match parser.next() {
-                Ok(XmlEvent::StartElement {
-                    ref name,
-                    ref attributes,
-                    ..
-                }) => match name.local_name.as_str() {
-                    "parameteritem" => self.read_parameter_item(parser)?,
-                    "simplesect" => {
-                        let kind = read_xml_attribute(attributes, "kind");
-                        if kind.is_some() && kind.unwrap() == "return" {
-                            self.read_return(parser)?;
-                        } else {
-                            depth += 1;
-                        }
-                    }
-                    _ => depth += 1,
-                },
-                Ok(XmlEvent::EndElement { .. }) => {
-                    if depth == 0 {
-                        break;
+    /// Set the detailed description, the parameters and the returned value
+    /// are documented apart
+    pub fn set_detailed_description(&mut self, description: Vec<Block>) {
+        let mut blocks = Vec::new();
+        for block in description {
+            match block {
+                Block::Parameters {
+                    ref kind,
+                    ref items,
+                } if kind == "param" => {
+                    for item in items {
+                        self.add_parameter_description(item);
                     }
-                    depth -= 1;
                 }
-                Err(e) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        e.clone(),
-                    ))
+                Block::Section { kind, content, .. } if kind == "return" => {
+                    self.ret_description = non_empty(content)
                 }
-                _ => {}
+                _ => blocks.push(block),
             }
         }
-        Ok(())
+        self.detailed = non_empty(blocks);
     }
 
     pub fn read_param(
@@ -180,11 +117,13 @@ impl Function {
         let mut depth = 0;
         let mut ctype: Option<String> = Option::None;
         let mut declname: Option<String> = Option::None;
+        let mut defval: Option<String> = Option::None;
         loop {
             match parser.next() {
                 Ok(XmlEvent::StartElement { ref name, .. }) => match name.local_name.as_str() {
                     "type" => ctype = read_characters_only(parser)?,
                     "declname" => declname = read_characters_only(parser)?,
+                    "defval" => defval = read_characters_only(parser)?,
                     _ => depth += 1,
                 },
                 Ok(XmlEvent::EndElement { .. }) => {
@@ -203,8 +142,8 @@ impl Function {
             }
         }
 
-        if declname.is_some() && ctype.is_some() {
-            let mut param = match self.parameters.iter_mut().find(|r| r.name == declname) {
+        if let (Some(_), Some(ctype)) = (&declname, ctype) {
+            let param = match self.parameters.iter_mut().find(|r| r.name == declname) {
                 Some(val) => val,
                 None => {
                     let mut p = Parameter::new();
@@ -213,17 +152,21 @@ impl Function {
                     self.parameters.last_mut().unwrap()
                 }
             };
-            param.ctype = Some(ctype.unwrap());
+            param.ctype = Some(ctype);
+            param.default_value = defval;
         }
         Ok(())
     }
 
     pub fn read(
         parser: &mut EventReader<BufReader<File>>,
-        xml_attributes: &Vec<OwnedAttribute>,
+        xml_attributes: &[OwnedAttribute],
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<Function, std::io::Error> {
         let mut func = Function::new();
+        func.id = read_xml_attribute(xml_attributes, "id").unwrap_or_default();
         func.is_static = read_xml_attribute(xml_attributes, "static").unwrap_or_default() == "yes";
+        func.is_const = read_xml_attribute(xml_attributes, "const").unwrap_or_default() == "yes";
         func.access = match read_xml_attribute(xml_attributes, "prot") {
             Some(val) => access_from_str(val.as_str()).unwrap_or(Access::Private),
             None => Access::Private,
@@ -244,8 +187,10 @@ impl Function {
                         match name.local_name.as_str() {
                             "type" => func.ret_type = read_characters_only(parser)?,
                             "name" => func.name = read_characters_only(parser)?.unwrap_or_default(),
-                            "briefdescription" => func.brief = read_description(parser)?,
-                            "detaileddescription" => func.read_detailed_description(parser)?,
+                            "briefdescription" => func.brief = read_description(parser, warnings)?,
+                            "detaileddescription" => {
+                                func.read_detailed_description(parser, warnings)?
+                            }
                             "param" => func.read_param(parser)?,
                             "location" => {
                                 func.location = Some(Location::read(attributes));
@@ -258,10 +203,10 @@ impl Function {
                 Ok(XmlEvent::EndElement { ref name }) => {
                     if depth == 0 {
                         if name.local_name != "memberdef" {
-                            println!(
+                            warnings.push(ParseWarning::new(format!(
                                 "Inconsistency {}, expected memberdef",
                                 name.local_name.as_str()
-                            );
+                            )));
                         }
                         break;
                     }
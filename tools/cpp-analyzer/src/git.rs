@@ -0,0 +1,303 @@
+use crate::check::*;
+use crate::codeowners::*;
+use crate::common::*;
+use crate::diagnostic::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const SOURCE_EXTENSIONS: [&str; 14] = [
+    "h", "hh", "hpp", "hxx", "h++", "inl", "ipp", "c", "cc", "cpp", "cxx", "c++", "ixx", "idl",
+];
+
+/// Lines added or modified since a git reference, indexed by canonical file path
+#[derive(Debug)]
+pub struct ChangeSet {
+    hunks: HashMap<PathBuf, Vec<(i32, i32)>>,
+}
+
+fn run_git(folder: &Path, args: &[&str]) -> Result<String, std::io::Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(folder)
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse a `+c,d` hunk range into the first and last modified lines
+fn parse_hunk_range(range: &str) -> Option<(i32, i32)> {
+    let range = range.strip_prefix('+')?;
+    let (start, count) = match range.split_once(',') {
+        Some((start, count)) => (start.parse::<i32>().ok()?, count.parse::<i32>().ok()?),
+        None => (range.parse::<i32>().ok()?, 1),
+    };
+    if count == 0 {
+        return None;
+    }
+    Some((start, start + count - 1))
+}
+
+/// Folder of `input`, a folder or a file
+fn input_folder(input: &str) -> Option<&Path> {
+    let path = Path::new(input);
+    let folder = if path.is_file() { path.parent()? } else { path };
+    if folder.as_os_str().is_empty() {
+        Some(Path::new("."))
+    } else {
+        Some(folder)
+    }
+}
+
+/// Commit checked out in the repository containing `input`, a folder or a file
+pub fn head_commit(input: &str) -> Option<String> {
+    run_git(input_folder(input)?, &["rev-parse", "HEAD"])
+        .ok()
+        .map(|commit| String::from(commit.trim()))
+}
+
+impl ChangeSet {
+    /// Collect the changes between `reference` and the working tree of the
+    /// repositories containing `inputs`, folders or files
+    pub fn since(reference: &str, inputs: &[String]) -> Result<ChangeSet, std::io::Error> {
+        let mut changes = ChangeSet {
+            hunks: HashMap::new(),
+        };
+
+        let mut roots: Vec<PathBuf> = Vec::new();
+        for input in inputs {
+            let folder = match input_folder(input) {
+                Some(folder) => folder,
+                None => continue,
+            };
+            let root = run_git(folder, &["rev-parse", "--show-toplevel"])?;
+            // The hunk paths are canonical, so is the root they are compared with
+            let root = fs::canonicalize(root.trim()).unwrap_or(PathBuf::from(root.trim()));
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+
+        for root in roots {
+            let diff = run_git(
+                &root,
+                &[
+                    "-c",
+                    "core.quotepath=off",
+                    "diff",
+                    "--unified=0",
+                    "--no-color",
+                    "--no-ext-diff",
+                    "--src-prefix=a/",
+                    "--dst-prefix=b/",
+                    reference,
+                    "--",
+                ],
+            )?;
+            changes.read_diff(&root, diff.as_str());
+
+            // New files are not in the diff until they are added
+            let untracked = run_git(
+                &root,
+                &[
+                    "-c",
+                    "core.quotepath=off",
+                    "ls-files",
+                    "--others",
+                    "--exclude-standard",
+                ],
+            )?;
+            for file in untracked.lines().filter(|file| !file.is_empty()) {
+                let path = root.join(file);
+                let path = fs::canonicalize(&path).unwrap_or(path);
+                changes.hunks.entry(path).or_default().push((1, i32::MAX));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    fn read_diff(&mut self, root: &Path, diff: &str) {
+        let mut current: Option<PathBuf> = None;
+        for line in diff.lines() {
+            if let Some(file) = line.strip_prefix("+++ ") {
+                current = file
+                    .strip_prefix("b/")
+                    .map(|file| fs::canonicalize(root.join(file)).unwrap_or(root.join(file)));
+            } else if line.starts_with("@@ ") {
+                let range = line.split(' ').find(|r| r.starts_with('+'));
+                if let (Some(file), Some(range)) = (&current, range.and_then(parse_hunk_range)) {
+                    self.hunks.entry(file.clone()).or_default().push(range);
+                }
+            }
+        }
+    }
+
+    /// Changed source files among `inputs` or located under one of them
+    pub fn files(&self, inputs: &[String]) -> Vec<String> {
+        let inputs: Vec<PathBuf> = inputs
+            .iter()
+            .filter_map(|f| fs::canonicalize(f).ok())
+            .collect();
+        let mut files: Vec<String> = self
+            .hunks
+            .keys()
+            .filter(|file| file.is_file())
+            .filter(|file| inputs.iter().any(|input| file.starts_with(input)))
+            .filter(|file| match file.extension() {
+                Some(ext) => {
+                    SOURCE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str())
+                }
+                None => false,
+            })
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
+
+    pub fn contains(&self, location: &Location) -> bool {
+        let file = match fs::canonicalize(&location.file) {
+            Ok(file) => file,
+            Err(_) => return false,
+        };
+        match self.hunks.get(&file) {
+            Some(ranges) => ranges
+                .iter()
+                .any(|(first, last)| *first <= location.line && location.line <= *last),
+            None => false,
+        }
+    }
+}
+
+/// Forward only the errors located in lines changed since a git reference
+pub struct ChangedLinesErrorWriter {
+    pub changes: ChangeSet,
+    pub inner: Box<dyn ErrorWriter>,
+}
+
+impl ErrorWriter for ChangedLinesErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        match &diagnostic.location {
+            Some(loc) if self.changes.contains(loc) => self.inner.append(diagnostic),
+            _ => Ok(()),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        self.inner.finish()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// Author and owners of the lines of the files of git repositories
+#[derive(Debug, Default)]
+pub struct Attribution {
+    /// Repository root of each folder
+    roots: HashMap<PathBuf, Option<PathBuf>>,
+    /// Author of each line of each file
+    authors: HashMap<PathBuf, Vec<String>>,
+    /// CODEOWNERS of each repository root
+    owners: HashMap<PathBuf, Option<CodeOwners>>,
+}
+
+/// Authors of the lines of `git blame --line-porcelain`
+fn parse_blame(blame: &str) -> Vec<String> {
+    blame
+        .lines()
+        .filter_map(|line| line.strip_prefix("author "))
+        .map(String::from)
+        .collect()
+}
+
+impl Attribution {
+    pub fn new() -> Attribution {
+        Attribution::default()
+    }
+
+    fn root(&mut self, folder: &Path) -> Option<PathBuf> {
+        self.roots
+            .entry(folder.to_path_buf())
+            .or_insert_with(|| {
+                run_git(folder, &["rev-parse", "--show-toplevel"])
+                    .ok()
+                    .map(|root| fs::canonicalize(root.trim()).unwrap_or(PathBuf::from(root.trim())))
+            })
+            .clone()
+    }
+
+    /// Set the author of the line and the owners of the file of `diagnostic`
+    pub fn attribute(&mut self, diagnostic: &mut Diagnostic) {
+        let location = match &diagnostic.location {
+            Some(location) => location,
+            None => return,
+        };
+        let file = match fs::canonicalize(&location.file) {
+            Ok(file) => file,
+            Err(_) => return,
+        };
+        let root = match file.parent().and_then(|folder| self.root(folder)) {
+            Some(root) => root,
+            None => return,
+        };
+        let relative = match file.strip_prefix(&root) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => return,
+        };
+
+        let authors = self.authors.entry(file.clone()).or_insert_with(|| {
+            run_git(
+                &root,
+                &["blame", "--line-porcelain", "--", relative.as_str()],
+            )
+            .map(|blame| parse_blame(&blame))
+            .unwrap_or_default()
+        });
+        if location.line > 0 {
+            diagnostic.author = authors.get(location.line as usize - 1).cloned();
+        }
+
+        let owners = self
+            .owners
+            .entry(root.clone())
+            .or_insert_with(|| CodeOwners::find(&root).ok().flatten());
+        if let Some(owners) = owners {
+            diagnostic.owners = owners.owners(&relative).to_vec();
+        }
+    }
+}
+
+/// Forward the errors with the author of their line and the owners of their file
+pub struct AttributionErrorWriter {
+    pub attribution: Attribution,
+    pub inner: Box<dyn ErrorWriter>,
+}
+
+impl ErrorWriter for AttributionErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        let mut diagnostic = diagnostic.clone();
+        self.attribution.attribute(&mut diagnostic);
+        self.inner.append(&diagnostic)
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        self.inner.finish()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
@@ -0,0 +1,136 @@
+use crate::common::*;
+use crate::description::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use xml::reader::{EventReader, XmlEvent};
+
+/// Doxygen group declared with `@defgroup`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Group {
+    pub id: String,
+    pub name: String,
+    pub title: Option<String>,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
+    /// Ids of the classes and structs of the group
+    pub classes: Vec<String>,
+    /// Ids of the functions, enums and other members of the group
+    pub members: Vec<String>,
+    /// Ids of the subgroups
+    pub groups: Vec<String>,
+    pub location: Option<Location>,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Group::new()
+    }
+}
+
+impl Group {
+    pub fn new() -> Group {
+        Group {
+            id: String::new(),
+            name: String::new(),
+            title: Option::None,
+            brief: Option::None,
+            detailed: Option::None,
+            classes: Vec::new(),
+            members: Vec::new(),
+            groups: Vec::new(),
+            location: Option::None,
+        }
+    }
+
+    fn read_content(
+        &mut self,
+        parser: &mut EventReader<BufReader<File>>,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<(), std::io::Error> {
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) => match name.local_name.as_str() {
+                    "compoundname" => self.name = read_characters_only(parser)?.unwrap_or_default(),
+                    "title" => {
+                        self.title = read_characters_only(parser)?
+                            .map(|title| String::from(title.trim()))
+                            .filter(|title| !title.is_empty())
+                    }
+                    "innerclass" => {
+                        if let Some(id) = read_xml_attribute(attributes, "refid") {
+                            self.classes.push(id);
+                        }
+                        skip_element(parser)?;
+                    }
+                    "innergroup" => {
+                        if let Some(id) = read_xml_attribute(attributes, "refid") {
+                            self.groups.push(id);
+                        }
+                        skip_element(parser)?;
+                    }
+                    "memberdef" => {
+                        if let Some(id) = read_xml_attribute(attributes, "id") {
+                            self.members.push(id);
+                        }
+                        skip_element(parser)?;
+                    }
+                    "briefdescription" => self.brief = read_description(parser, warnings)?,
+                    "detaileddescription" => self.detailed = read_description(parser, warnings)?,
+                    "location" => self.location = Some(Location::read(attributes)),
+                    _ => {}
+                },
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "compounddef" => {
+                    break;
+                }
+                Ok(XmlEvent::EndDocument) => break,
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(filename: &str, warnings: &mut Vec<ParseWarning>) -> Result<Group, std::io::Error> {
+        let file = std::io::BufReader::new(File::open(filename)?);
+        let mut parser = EventReader::new(file);
+
+        let mut group = Group::new();
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) if name.local_name == "compounddef" => {
+                    group.id = read_xml_attribute(attributes, "id").unwrap_or_default();
+                    group.read_content(&mut parser, warnings)?;
+                }
+                Ok(XmlEvent::EndDocument) => break,
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(group)
+    }
+
+    /// Title if any, name otherwise
+    pub fn display_name(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+}
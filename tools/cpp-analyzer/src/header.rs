@@ -0,0 +1,2201 @@
+//! Built-in front end: the sources are parsed with tree-sitter and their
+//! doxygen comments attached to the declarations, giving the same model as
+//! doxygen without running it.
+//!
+//! It follows the settings of the generated Doxyfile: private members are not
+//! extracted, a single `///` line is a brief description and locations are
+//! absolute paths. Preprocessor conditions are evaluated with the given
+//! defines and the macros defined before them, `NAME=value` defines are
+//! substituted and the Qt macros are removed before parsing.
+
+use crate::attribute::*;
+use crate::class::*;
+use crate::comment::*;
+use crate::common::*;
+use crate::description::*;
+use crate::diagnostic::*;
+use crate::enumerated::*;
+use crate::file::*;
+use crate::function::*;
+use crate::git::SOURCE_EXTENSIONS;
+use crate::group::*;
+use crate::paths::*;
+use crate::project::*;
+use crate::property::*;
+
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+/// Keywords of `Q_PROPERTY` following the type and the name
+const PROPERTY_KEYWORDS: [&str; 14] = [
+    "READ",
+    "WRITE",
+    "MEMBER",
+    "RESET",
+    "NOTIFY",
+    "REVISION",
+    "DESIGNABLE",
+    "SCRIPTABLE",
+    "STORED",
+    "USER",
+    "BINDABLE",
+    "CONSTANT",
+    "FINAL",
+    "REQUIRED",
+];
+
+/// Name escaped the way doxygen builds its ids, with `CASE_SENSE_NAMES = NO`
+fn escape(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.chars() {
+        match escaped_char(c) {
+            Some(code) => id.push_str(code),
+            None if c.is_ascii_uppercase() => {
+                id.push('_');
+                id.push(c.to_ascii_lowercase());
+            }
+            None => id.push(c),
+        }
+    }
+    id
+}
+
+fn join(scope: &str, name: &str) -> String {
+    match scope.is_empty() {
+        true => String::from(name),
+        false => format!("{}::{}", scope, name),
+    }
+}
+
+fn compact(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Qualified name without its template arguments
+fn strip_template_arguments(name: &str) -> String {
+    let mut depth = 0;
+    let mut stripped = String::new();
+    for c in name.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            c if depth == 0 && !c.is_whitespace() => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+fn row(node: Node) -> i32 {
+    node.start_position().row as i32 + 1
+}
+
+/// Named children of `node` that are not one of its fields, the content of
+/// a list or of a preprocessor branch
+fn named_children(node: Node) -> Vec<Node> {
+    let mut children = Vec::new();
+    let mut cursor = node.walk();
+    if cursor.goto_first_child() {
+        loop {
+            if cursor.node().is_named() && cursor.field_name().is_none() {
+                children.push(cursor.node());
+            }
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+    children
+}
+
+/// Regular expression of a doxygen `EXCLUDE_PATTERNS` wildcard, matched
+/// against the absolute path
+fn wildcard_regex(pattern: &str) -> Option<Regex> {
+    let expression = regex::escape(pattern)
+        .replace("\\*", ".*")
+        .replace("\\?", ".");
+    Regex::new(&format!("^{}$", expression)).ok()
+}
+
+fn is_source(path: &Path) -> bool {
+    match path.extension() {
+        Some(ext) => {
+            let ext = ext.to_string_lossy().to_lowercase();
+            ext != "idl" && SOURCE_EXTENSIONS.contains(&ext.as_str())
+        }
+        None => false,
+    }
+}
+
+fn collect_sources(path: &Path, files: &mut Vec<String>) -> Result<(), Error> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_sources(&entry?.path(), files)?;
+        }
+    } else if is_source(path) {
+        files.push(normalize_path(&path.to_string_lossy()));
+    }
+    Ok(())
+}
+
+/// C and C++ files of the inputs, folders are read recursively
+fn source_files(inputs: &[String], exclude: &[String]) -> Result<Vec<String>, Error> {
+    let patterns: Vec<Regex> = exclude.iter().filter_map(|p| wildcard_regex(p)).collect();
+    let mut files = Vec::new();
+    for input in inputs {
+        collect_sources(&fs::canonicalize(input)?, &mut files)?;
+    }
+    files.retain(|file| !patterns.iter().any(|pattern| pattern.is_match(file)));
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Source rewritten so that tree-sitter can parse it, with the same lines
+struct Preprocessed {
+    text: String,
+    /// Kind of the functions following the `signals:` and `slots:` labels,
+    /// by row
+    kinds: HashMap<usize, FunctionKind>,
+}
+
+/// Rewriting of the constructs that are not C++ until preprocessed
+struct Rewriter {
+    substitutions: Vec<(Regex, String)>,
+    signals: Regex,
+    slots: Regex,
+    /// Export macros found between `class` and the class name
+    export: Option<Regex>,
+    /// Upper case names that can only be an export macro, followed by the
+    /// class name and its base list, body or `final`
+    unknown_export: Regex,
+    qt: Regex,
+}
+
+/// `line` with the macros matched by `export`, its third group, replaced by
+/// spaces
+fn blank_export_macros(export: &Regex, line: &str) -> String {
+    export
+        .replace_all(line, |caps: &Captures| {
+            format!(
+                "{}{}{}{}",
+                &caps[1],
+                &caps[2],
+                " ".repeat(caps[3].len()),
+                &caps[4]
+            )
+        })
+        .into_owned()
+}
+
+impl Rewriter {
+    fn new(defines: &[String], export_macros: &[String]) -> Rewriter {
+        let mut substitutions = Vec::new();
+        for define in defines {
+            if let Some((name, value)) = define.split_once('=') {
+                if !name.contains('(') {
+                    let word = format!(r"\b{}\b", regex::escape(name.trim()));
+                    if let Ok(regex) = Regex::new(&word) {
+                        substitutions.push((regex, String::from(value)));
+                    }
+                }
+            }
+        }
+        let names: Vec<String> = export_macros
+            .iter()
+            .map(|name| regex::escape(name))
+            .collect();
+        let export = match names.is_empty() {
+            true => None,
+            false => Regex::new(&format!(
+                r"\b(class|struct)(\s+)({})(\s+[A-Za-z_])",
+                names.join("|")
+            ))
+            .ok(),
+        };
+        Rewriter {
+            substitutions,
+            signals: Regex::new(r"^(\s*)(?:signals|Q_SIGNALS)\s*:").unwrap(),
+            slots: Regex::new(r"^(\s*)(public|protected|private)\s+(?:slots|Q_SLOTS)\s*:").unwrap(),
+            export,
+            unknown_export: Regex::new(
+                r"\b(class|struct)(\s+)([A-Z][A-Z0-9_]+)(\s+[A-Za-z_]\w*\s*(?:final\b|:[^:]|:$|\{|$))",
+            )
+            .unwrap(),
+            qt: Regex::new(r"\bQ_[A-Z0-9_]+\b").unwrap(),
+        }
+    }
+
+    fn rewrite(&self, content: &str) -> Preprocessed {
+        let mut kinds = HashMap::new();
+        let mut lines = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') || trimmed.starts_with("//") {
+                lines.push(String::from(line));
+                continue;
+            }
+            let mut line = String::from(line);
+            for (regex, value) in &self.substitutions {
+                line = regex.replace_all(&line, value.as_str()).into_owned();
+            }
+            if self.signals.is_match(&line) {
+                line = self.signals.replace(&line, "${1}public:").into_owned();
+                kinds.insert(index, FunctionKind::Signal);
+            } else if self.slots.is_match(&line) {
+                line = self.slots.replace(&line, "${1}${2}:").into_owned();
+                kinds.insert(index, FunctionKind::Slot);
+            }
+            // Export macros, as in class SDK_EXPORT Widget
+            if let Some(export) = &self.export {
+                line = blank_export_macros(export, &line);
+            }
+            line = blank_export_macros(&self.unknown_export, &line);
+            lines.push(line);
+        }
+        // A directive on the last line needs its end of line
+        lines.push(String::new());
+        Preprocessed {
+            text: self.hide_qt_macros(&lines.join("\n")),
+            kinds,
+        }
+    }
+
+    /// Blank the Qt macros and their arguments, `Q_PROPERTY` is kept as a
+    /// comment to be read later
+    fn hide_qt_macros(&self, text: &str) -> String {
+        let mut output = String::new();
+        let mut pos = 0;
+        for found in self.qt.find_iter(text) {
+            if found.start() < pos {
+                continue;
+            }
+            let line_start = text[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+            let before = text[line_start..found.start()].trim_start();
+            if ["#", "//", "/*", "*"].iter().any(|p| before.starts_with(p)) {
+                continue;
+            }
+            let mut end = found.end();
+            let rest = &text[end..];
+            let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+            if rest[spaces..].starts_with('(') {
+                let mut depth = 0;
+                for (i, c) in rest[spaces..].char_indices() {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end += spaces + i + 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            output.push_str(&text[pos..found.start()]);
+            let hidden = &text[found.start()..end];
+            if found.as_str() == "Q_PROPERTY" {
+                output.push_str("/*");
+                output.push_str(hidden);
+                output.push_str("*/");
+            } else {
+                output.extend(hidden.chars().map(|c| if c == '\n' { '\n' } else { ' ' }));
+            }
+            pos = end;
+        }
+        output.push_str(&text[pos..]);
+        output
+    }
+}
+
+/// Name of a declarator with the pointer and reference marks of its type
+struct Declarator<'t> {
+    name: Option<Node<'t>>,
+    marks: String,
+    /// Parameters of a function, `None` for variables
+    parameters: Option<Node<'t>>,
+    /// Function pointers are variables
+    function_pointer: bool,
+    /// Const member function
+    is_const: bool,
+}
+
+/// Whether a function declarator ends with a `const` qualifier
+fn has_const_qualifier(node: Node) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|child| {
+        child.kind() == "type_qualifier" && child.child(0).is_some_and(|q| q.kind() == "const")
+    });
+    found
+}
+
+impl<'t> Declarator<'t> {
+    fn read(mut node: Node<'t>) -> Declarator<'t> {
+        let mut declarator = Declarator {
+            name: None,
+            marks: String::new(),
+            parameters: None,
+            function_pointer: false,
+            is_const: false,
+        };
+        loop {
+            let inner = match node.kind() {
+                "pointer_declarator"
+                | "reference_declarator"
+                | "abstract_pointer_declarator"
+                | "abstract_reference_declarator" => {
+                    if let Some(mark) = node.child(0) {
+                        declarator.marks.push_str(mark.kind());
+                    }
+                    match node.child_by_field_name("declarator") {
+                        Some(inner) => Some(inner),
+                        None => node
+                            .named_child(node.named_child_count().saturating_sub(1))
+                            .filter(|inner| inner.kind() != "type_qualifier"),
+                    }
+                }
+                "init_declarator" | "array_declarator" => node.child_by_field_name("declarator"),
+                "attributed_declarator" | "parenthesized_declarator" => node.named_child(0),
+                "function_declarator" => {
+                    let inner = node.child_by_field_name("declarator");
+                    if inner.map(|inner| inner.kind()) == Some("parenthesized_declarator") {
+                        declarator.function_pointer = true;
+                    } else if declarator.parameters.is_none() && !declarator.function_pointer {
+                        declarator.parameters = node.child_by_field_name("parameters");
+                        declarator.is_const = has_const_qualifier(node);
+                    }
+                    inner
+                }
+                "operator_cast" => {
+                    let function = node.child_by_field_name("declarator");
+                    declarator.parameters =
+                        function.and_then(|d| d.child_by_field_name("parameters"));
+                    declarator.is_const = function.is_some_and(has_const_qualifier);
+                    declarator.name = Some(node);
+                    None
+                }
+                "identifier"
+                | "field_identifier"
+                | "destructor_name"
+                | "operator_name"
+                | "qualified_identifier"
+                | "template_function"
+                | "type_identifier" => {
+                    declarator.name = Some(node);
+                    None
+                }
+                _ => None,
+            };
+            match inner {
+                Some(inner) => node = inner,
+                None => return declarator,
+            }
+        }
+    }
+
+    fn is_function(&self) -> bool {
+        self.parameters.is_some() && !self.function_pointer
+    }
+}
+
+/// Class being read with the state of its member list
+struct ClassContext {
+    class: Class,
+    access: Access,
+    kind: FunctionKind,
+}
+
+/// Member a trailing `///<` block documents
+#[derive(Clone, Copy)]
+enum Last {
+    None,
+    Attribute(usize),
+    Function(usize),
+    Property(usize),
+    Macro(usize),
+}
+
+/// Doc comment blocks read before the declaration they document
+#[derive(Default)]
+struct Comments {
+    block: Option<CommentBlock>,
+    leading: Option<DocComment>,
+}
+
+#[derive(Clone, Copy)]
+enum Membership {
+    Class,
+    Member,
+    Group,
+}
+
+/// Documented out-of-line definition of a member function
+struct Definition {
+    scope: String,
+    name: String,
+    parameters: usize,
+    doc: DocComment,
+}
+
+/// Descriptions of an entity, references are resolved from its scope
+struct Documentation<'a> {
+    id: &'a str,
+    scope: String,
+    brief: &'a mut Option<Description>,
+    detailed: Option<&'a mut Option<Description>>,
+    /// Parameter and returned value descriptions
+    others: Vec<&'a mut Option<Description>>,
+}
+
+fn set_description(
+    brief: &mut Option<Description>,
+    detailed: &mut Option<Description>,
+    doc: &DocComment,
+) {
+    if !doc.brief.is_empty() {
+        *brief = non_empty(doc.brief.clone());
+    }
+    if !doc.detailed.is_empty() {
+        *detailed = non_empty(doc.detailed.clone());
+    }
+}
+
+fn set_function_description(function: &mut Function, doc: &DocComment) {
+    if !doc.brief.is_empty() {
+        function.brief = non_empty(doc.brief.clone());
+    }
+    if !doc.detailed.is_empty() {
+        function.set_detailed_description(doc.detailed.clone());
+    }
+}
+
+type Index = HashMap<String, (String, &'static str)>;
+
+/// Look a documented name up from `scope` and its enclosing scopes
+fn lookup<'i>(index: &'i Index, scope: &str, target: &str) -> Option<&'i (String, &'static str)> {
+    let target = target.replace('#', "::");
+    let target = target.split('(').next().unwrap_or_default();
+    let target = target.trim_start_matches("::");
+    if target.is_empty() {
+        return None;
+    }
+    let mut scope = scope;
+    loop {
+        if let Some(found) = index.get(&join(scope, target)) {
+            return Some(found);
+        }
+        if scope.is_empty() {
+            return None;
+        }
+        scope = split_scope(scope).0;
+    }
+}
+
+/// Words of a see also section that doxygen links, the names with a scope
+/// or a call and the compounds
+fn autolink(index: &Index, scope: &str, text: &str, inlines: &mut Vec<Inline>) {
+    let mut plain = String::new();
+    for word in text.split_inclusive(char::is_whitespace) {
+        let trimmed = word.trim_end();
+        let target = trimmed.trim_end_matches(['.', ',', ';', ':']);
+        let found = match target.chars().any(char::is_alphanumeric) {
+            true => lookup(index, scope, target),
+            false => None,
+        };
+        match found {
+            Some((id, kindref))
+                if *kindref == "compound"
+                    || target.contains("::")
+                    || target.contains('#')
+                    || target.ends_with(')') =>
+            {
+                if !plain.is_empty() {
+                    inlines.push(Inline::Text(std::mem::take(&mut plain)));
+                }
+                inlines.push(Inline::Ref(Reference {
+                    kind: ReferenceKind::Ref,
+                    refid: Some(id.clone()),
+                    kindref: Some(String::from(*kindref)),
+                    text: String::from(target),
+                }));
+                plain.push_str(&word[target.len()..]);
+            }
+            _ => plain.push_str(word),
+        }
+    }
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(plain));
+    }
+}
+
+/// Resolve the references of `blocks`, unresolved `\ref` are written as
+/// plain text like doxygen does
+fn resolve_blocks(index: &Index, scope: &str, blocks: Vec<Block>, see: bool) -> Vec<Block> {
+    blocks
+        .into_iter()
+        .map(|block| match block {
+            Block::Paragraph(inlines) => {
+                let mut resolved = Vec::new();
+                for inline in inlines {
+                    match inline {
+                        Inline::Ref(reference) if reference.refid.is_none() => {
+                            match lookup(index, scope, &reference.text) {
+                                Some((id, kindref)) => resolved.push(Inline::Ref(Reference {
+                                    refid: Some(id.clone()),
+                                    kindref: Some(String::from(*kindref)),
+                                    ..reference
+                                })),
+                                None => resolved.push(Inline::Text(reference.text)),
+                            }
+                        }
+                        Inline::Text(text) if see => autolink(index, scope, &text, &mut resolved),
+                        inline => resolved.push(inline),
+                    }
+                }
+                Block::Paragraph(resolved)
+            }
+            Block::List { ordered, items } => Block::List {
+                ordered,
+                items: items
+                    .into_iter()
+                    .map(|item| resolve_blocks(index, scope, item, see))
+                    .collect(),
+            },
+            Block::Section {
+                kind,
+                title,
+                content,
+            } => {
+                let see = kind == "see";
+                Block::Section {
+                    kind,
+                    title,
+                    content: resolve_blocks(index, scope, content, see),
+                }
+            }
+            Block::Parameters { kind, items } => Block::Parameters {
+                kind,
+                items: items
+                    .into_iter()
+                    .map(|item| ParameterItem {
+                        description: resolve_blocks(index, scope, item.description, see),
+                        ..item
+                    })
+                    .collect(),
+            },
+            block => block,
+        })
+        .collect()
+}
+
+fn resolve_description(index: &Index, scope: &str, description: &mut Option<Description>) {
+    if let Some(current) = description.take() {
+        *description = non_empty(resolve_blocks(index, scope, current.blocks, false));
+    }
+}
+
+fn enum_documentations<'a>(
+    enumerated: &'a mut Enumerated,
+    scope: &str,
+    documentations: &mut Vec<Documentation<'a>>,
+) {
+    let Enumerated {
+        id,
+        brief,
+        detailed,
+        values,
+        ..
+    } = enumerated;
+    documentations.push(Documentation {
+        id,
+        scope: String::from(scope),
+        brief,
+        detailed: Some(detailed),
+        others: Vec::new(),
+    });
+    for value in values {
+        documentations.push(Documentation {
+            id: &value.id,
+            scope: String::from(scope),
+            brief: &mut value.brief,
+            detailed: None,
+            others: Vec::new(),
+        });
+    }
+}
+
+/// Entities read from all the sources
+struct Builder {
+    rewriter: Rewriter,
+    /// Macros given as defines
+    defined: HashSet<String>,
+    classes: Vec<Class>,
+    enums: Vec<Enumerated>,
+    groups: Vec<Group>,
+    files: Vec<SourceFile>,
+    diagnostics: Vec<Diagnostic>,
+    /// Id and kind of reference of the documented names
+    index: Index,
+    ids: HashSet<String>,
+    anonymous_enums: usize,
+    memberships: Vec<(String, String, Membership)>,
+    /// Targets of the `@copydoc` of each entity, by id
+    copies: HashMap<String, Vec<String>>,
+    definitions: Vec<Definition>,
+    /// Qualified names and ids of the properties, indexed last as their
+    /// READ function often has the same name
+    properties: Vec<(String, String)>,
+}
+
+impl Builder {
+    /// `macros` are the names defined by the sources, any of them, like the
+    /// defines and the configured export macros, may be an export macro
+    fn new(options: &LoadOptions, macros: &[String]) -> Builder {
+        let defined: HashSet<String> = options
+            .defines
+            .iter()
+            .map(|define| {
+                let name = define.split(['=', '(']).next().unwrap_or_default();
+                String::from(name.trim())
+            })
+            .collect();
+        let mut export_macros: Vec<String> = options.export_macros.clone();
+        for name in defined.iter().chain(macros) {
+            if !export_macros.contains(name) {
+                export_macros.push(name.clone());
+            }
+        }
+        Builder {
+            rewriter: Rewriter::new(&options.defines, &export_macros),
+            defined,
+            classes: Vec::new(),
+            enums: Vec::new(),
+            groups: Vec::new(),
+            files: Vec::new(),
+            diagnostics: Vec::new(),
+            index: HashMap::new(),
+            ids: HashSet::new(),
+            anonymous_enums: 0,
+            memberships: Vec::new(),
+            copies: HashMap::new(),
+            definitions: Vec::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// `id`, with a suffix if it is already used by an overload
+    fn unique_id(&mut self, id: String) -> String {
+        let mut unique = id.clone();
+        let mut count = 1;
+        while self.ids.contains(&unique) {
+            count += 1;
+            unique = format!("{}_{}", id, count);
+        }
+        self.ids.insert(unique.clone());
+        unique
+    }
+
+    fn member_id(&mut self, compound: &str, name: &str) -> String {
+        self.unique_id(format!("{}_1{}", compound, escape(name)))
+    }
+
+    fn add_name(&mut self, name: String, id: &str, kindref: &'static str) {
+        self.index
+            .entry(name)
+            .or_insert_with(|| (String::from(id), kindref));
+    }
+
+    fn group(&mut self, name: &str) -> &mut Group {
+        let position = match self.groups.iter().position(|group| group.name == name) {
+            Some(position) => position,
+            None => {
+                let mut group = Group::new();
+                group.id = format!("group__{}", escape(name));
+                group.name = String::from(name);
+                self.add_name(group.name.clone(), &group.id, "compound");
+                self.groups.push(group);
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[position]
+    }
+
+    fn read_file(
+        &mut self,
+        parser: &mut Parser,
+        path: &str,
+        content: &str,
+        warnings: &mut Vec<ParseWarning>,
+    ) {
+        let source = self.rewriter.rewrite(content);
+        let tree = match parser.parse(&source.text, None) {
+            Some(tree) => tree,
+            None => {
+                warnings.push(ParseWarning::new(format!("Cannot parse {}, skipped", path)));
+                return;
+            }
+        };
+        let root = tree.root_node();
+        if root.has_error() {
+            let mut errors = Vec::new();
+            syntax_errors(root, &mut errors);
+            let mut warning = ParseWarning::new(format!(
+                "{} syntax error(s) in {}, the declarations around them may be missing",
+                errors.len().max(1),
+                path
+            ));
+            warning.location = Some(Location {
+                file: String::from(path),
+                line: errors.first().copied().unwrap_or(1),
+            });
+            warnings.push(warning);
+        }
+
+        let name = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut file = SourceFile::new();
+        file.id = self.unique_id(escape(&name));
+        file.name = name;
+        file.location = Some(Location {
+            file: String::from(path),
+            line: 1,
+        });
+        let defined = self.defined.clone();
+        let mut reader = FileReader {
+            builder: self,
+            path: String::from(path),
+            source: source.text.as_str(),
+            kinds: source.kinds.clone(),
+            file,
+            defined,
+            groups: Vec::new(),
+        };
+        reader.list(root, "", &mut None);
+        let file = reader.file;
+        self.files.push(file);
+    }
+
+    /// Give the documentation of the out-of-line definitions to the
+    /// undocumented declarations
+    fn apply_definitions(&mut self) {
+        for definition in std::mem::take(&mut self.definitions) {
+            let (class_name, function_name) = split_scope(&definition.name);
+            let class_name = strip_template_arguments(class_name);
+            let mut scope = definition.scope.as_str();
+            let position = loop {
+                let name = join(scope, &class_name);
+                if let Some(position) = self.classes.iter().position(|c| c.name == name) {
+                    break Some(position);
+                }
+                if scope.is_empty() {
+                    break None;
+                }
+                scope = split_scope(scope).0;
+            };
+            let class = match position {
+                Some(position) => &mut self.classes[position],
+                None => continue,
+            };
+            let function = class
+                .functions
+                .iter_mut()
+                .filter(|f| f.name == function_name && f.brief.is_none() && f.detailed.is_none())
+                .min_by_key(|f| f.parameters.len() != definition.parameters);
+            if let Some(function) = function {
+                set_function_description(function, &definition.doc);
+                if !definition.doc.copies.is_empty() {
+                    self.copies
+                        .insert(function.id.clone(), definition.doc.copies.clone());
+                }
+            }
+        }
+    }
+
+    fn apply_memberships(&mut self) {
+        for (name, id, membership) in std::mem::take(&mut self.memberships) {
+            let group = match self.groups.iter_mut().find(|group| group.name == name) {
+                Some(group) => group,
+                None => continue,
+            };
+            let list = match membership {
+                Membership::Class => &mut group.classes,
+                Membership::Member => &mut group.members,
+                Membership::Group => &mut group.groups,
+            };
+            if !list.contains(&id) {
+                list.push(id);
+            }
+        }
+    }
+
+    fn documentations(&mut self) -> Vec<Documentation<'_>> {
+        let mut documentations = Vec::new();
+        for class in self.classes.iter_mut() {
+            let Class {
+                id,
+                name,
+                brief,
+                detailed,
+                attributes,
+                functions,
+                properties,
+                enums,
+                ..
+            } = class;
+            let scope = name.as_str();
+            documentations.push(Documentation {
+                id,
+                scope: String::from(scope),
+                brief,
+                detailed: Some(detailed),
+                others: Vec::new(),
+            });
+            for a in attributes {
+                documentations.push(Documentation {
+                    id: &a.id,
+                    scope: String::from(scope),
+                    brief: &mut a.brief,
+                    detailed: Some(&mut a.detailed),
+                    others: Vec::new(),
+                });
+            }
+            for p in properties {
+                documentations.push(Documentation {
+                    id: &p.id,
+                    scope: String::from(scope),
+                    brief: &mut p.brief,
+                    detailed: Some(&mut p.detailed),
+                    others: Vec::new(),
+                });
+            }
+            for f in functions {
+                let Function {
+                    id,
+                    brief,
+                    detailed,
+                    ret_description,
+                    parameters,
+                    ..
+                } = f;
+                let mut others = vec![ret_description];
+                others.extend(parameters.iter_mut().map(|p| &mut p.description));
+                documentations.push(Documentation {
+                    id,
+                    scope: String::from(scope),
+                    brief,
+                    detailed: Some(detailed),
+                    others,
+                });
+            }
+            for enumerated in enums {
+                enum_documentations(enumerated, scope, &mut documentations);
+            }
+        }
+        for enumerated in self.enums.iter_mut() {
+            let scope = String::from(split_scope(&enumerated.full_name).0);
+            enum_documentations(enumerated, &scope, &mut documentations);
+        }
+        for group in self.groups.iter_mut() {
+            documentations.push(Documentation {
+                id: &group.id,
+                scope: String::new(),
+                brief: &mut group.brief,
+                detailed: Some(&mut group.detailed),
+                others: Vec::new(),
+            });
+        }
+        for file in self.files.iter_mut() {
+            let SourceFile {
+                id,
+                brief,
+                detailed,
+                macros,
+                ..
+            } = file;
+            documentations.push(Documentation {
+                id,
+                scope: String::new(),
+                brief,
+                detailed: Some(detailed),
+                others: Vec::new(),
+            });
+            for define in macros {
+                documentations.push(Documentation {
+                    id: &define.id,
+                    scope: String::new(),
+                    brief: &mut define.brief,
+                    detailed: Some(&mut define.detailed),
+                    others: Vec::new(),
+                });
+            }
+        }
+        documentations
+    }
+
+    /// Resolve the references once all the names are known, then copy the
+    /// documentation named by `@copydoc`
+    fn resolve(&mut self) {
+        for (name, id) in std::mem::take(&mut self.properties) {
+            self.add_name(name, &id, "member");
+        }
+        let index = std::mem::take(&mut self.index);
+        let copies = std::mem::take(&mut self.copies);
+        let mut documentations = self.documentations();
+        for documentation in documentations.iter_mut() {
+            let scope = documentation.scope.as_str();
+            resolve_description(&index, scope, documentation.brief);
+            if let Some(detailed) = documentation.detailed.as_deref_mut() {
+                resolve_description(&index, scope, detailed);
+            }
+            for other in documentation.others.iter_mut() {
+                resolve_description(&index, scope, other);
+            }
+        }
+
+        // Snapshot of the descriptions, the returned value and parameters
+        // are copied when the signatures match
+        type Copied = (
+            Option<Description>,
+            Option<Description>,
+            Vec<Option<Description>>,
+        );
+        let copied: HashMap<String, Copied> = documentations
+            .iter()
+            .map(|documentation| {
+                let detailed = documentation.detailed.as_deref().cloned().flatten();
+                let others = documentation.others.iter().map(|o| (**o).clone()).collect();
+                (
+                    String::from(documentation.id),
+                    (documentation.brief.clone(), detailed, others),
+                )
+            })
+            .collect();
+        for documentation in documentations.iter_mut() {
+            let targets = match copies.get(documentation.id) {
+                Some(targets) => targets,
+                None => continue,
+            };
+            for target in targets {
+                let (brief, detailed, others) = match lookup(&index, &documentation.scope, target)
+                    .and_then(|(id, _)| copied.get(id))
+                {
+                    Some(found) => found,
+                    None => continue,
+                };
+                if documentation.brief.is_none() {
+                    *documentation.brief = brief.clone();
+                }
+                if let Some(current) = documentation.detailed.as_deref_mut() {
+                    if current.is_none() {
+                        *current = detailed.clone();
+                    }
+                }
+                if documentation.others.len() == others.len() {
+                    for (current, other) in documentation.others.iter_mut().zip(others) {
+                        if current.is_none() {
+                            **current = other.clone();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(mut self, project: &mut Project) {
+        self.apply_definitions();
+        self.apply_memberships();
+        self.resolve();
+        for class in self.classes {
+            match class.is_struct {
+                true => project.objects.push_back(class),
+                false => project.classes.push_back(class),
+            }
+        }
+        project.enums.extend(self.enums);
+        project.groups.extend(self.groups);
+        project.files.extend(self.files);
+        project.doxygen_warnings = self.diagnostics;
+    }
+}
+
+/// Lines of the syntax errors found under `node`
+fn syntax_errors(node: Node, errors: &mut Vec<i32>) {
+    if node.is_error() || node.is_missing() {
+        errors.push(row(node));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.has_error() {
+            syntax_errors(child, errors);
+        }
+    }
+}
+
+/// Reading of the declarations of one file
+struct FileReader<'a> {
+    builder: &'a mut Builder,
+    path: String,
+    source: &'a str,
+    kinds: HashMap<usize, FunctionKind>,
+    file: SourceFile,
+    /// Macros defined so far, for the preprocessor conditions
+    defined: HashSet<String>,
+    /// Groups opened with `@{`, the innermost last
+    groups: Vec<Option<String>>,
+}
+
+impl<'a> FileReader<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.source[node.byte_range()]
+    }
+
+    fn location(&self, line: i32) -> Option<Location> {
+        Some(Location {
+            file: self.path.clone(),
+            line,
+        })
+    }
+
+    /// Declarations of a list, with the preprocessor branches that are
+    /// compiled and the `extern "C"` blocks flattened
+    fn flatten<'t>(&mut self, node: Node<'t>, nodes: &mut Vec<Node<'t>>) {
+        for child in named_children(node) {
+            self.item(child, nodes);
+        }
+    }
+
+    fn item<'t>(&mut self, node: Node<'t>, nodes: &mut Vec<Node<'t>>) {
+        match node.kind() {
+            "preproc_if" | "preproc_ifdef" => self.branch(node, nodes),
+            "linkage_specification" => match node.child_by_field_name("body") {
+                Some(body) if body.kind() == "declaration_list" => self.flatten(body, nodes),
+                Some(body) => nodes.push(body),
+                None => {}
+            },
+            "preproc_def" | "preproc_function_def" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.defined.insert(String::from(self.text(name)));
+                }
+                nodes.push(node);
+            }
+            "preproc_call" => {
+                let text = compact(self.text(node));
+                if let Some(name) = text.strip_prefix("#undef ") {
+                    self.defined.remove(name);
+                }
+            }
+            "preproc_include" => {}
+            _ => nodes.push(node),
+        }
+    }
+
+    fn branch<'t>(&mut self, node: Node<'t>, nodes: &mut Vec<Node<'t>>) {
+        if !self.condition(node) {
+            if let Some(alternative) = node.child_by_field_name("alternative") {
+                match alternative.kind() {
+                    "preproc_else" => self.flatten(alternative, nodes),
+                    _ => self.branch(alternative, nodes),
+                }
+            }
+            return;
+        }
+        let mut children = named_children(node);
+        // An include guard is not a macro of the file
+        let guard = match node.child(0) {
+            Some(directive) if directive.kind() == "#ifndef" => {
+                node.child_by_field_name("name").map(|name| self.text(name))
+            }
+            _ => None,
+        };
+        if let (Some(guard), Some(first)) = (guard, children.first()) {
+            if first.kind() == "preproc_def"
+                && first.child_by_field_name("value").is_none()
+                && first
+                    .child_by_field_name("name")
+                    .map(|name| self.text(name))
+                    == Some(guard)
+            {
+                self.defined.insert(String::from(guard));
+                children.remove(0);
+            }
+        }
+        for child in children {
+            self.item(child, nodes);
+        }
+    }
+
+    /// Whether the first branch of a conditional is compiled, the
+    /// expressions that cannot be evaluated are assumed true
+    fn condition(&self, node: Node) -> bool {
+        match node.kind() {
+            "preproc_ifdef" | "preproc_elifdef" => {
+                let defined = node
+                    .child_by_field_name("name")
+                    .is_some_and(|name| self.defined.contains(self.text(name)));
+                let negated = node
+                    .child(0)
+                    .is_some_and(|directive| directive.kind().ends_with("ndef"));
+                defined != negated
+            }
+            _ => node
+                .child_by_field_name("condition")
+                .is_none_or(|condition| self.evaluate(condition) != Some(false)),
+        }
+    }
+
+    fn evaluate(&self, node: Node) -> Option<bool> {
+        match node.kind() {
+            "number_literal" => Some(self.text(node).trim_end_matches(['u', 'U', 'l', 'L']) != "0"),
+            "true" => Some(true),
+            "false" => Some(false),
+            "identifier" => match self.defined.contains(self.text(node)) {
+                true => None,
+                false => Some(false),
+            },
+            "preproc_defined" => {
+                let name = named_children(node)
+                    .into_iter()
+                    .find(|n| n.kind() == "identifier")?;
+                Some(self.defined.contains(self.text(name)))
+            }
+            "parenthesized_expression" => self.evaluate(node.named_child(0)?),
+            "unary_expression" => {
+                let operator = node.child_by_field_name("operator")?;
+                match self.text(operator) {
+                    "!" => self
+                        .evaluate(node.child_by_field_name("argument")?)
+                        .map(|value| !value),
+                    _ => None,
+                }
+            }
+            "binary_expression" => {
+                let left = self.evaluate(node.child_by_field_name("left")?);
+                let right = self.evaluate(node.child_by_field_name("right")?);
+                match self.text(node.child_by_field_name("operator")?) {
+                    "&&" => match (left, right) {
+                        (Some(false), _) | (_, Some(false)) => Some(false),
+                        (Some(true), Some(true)) => Some(true),
+                        _ => None,
+                    },
+                    "||" => match (left, right) {
+                        (Some(true), _) | (_, Some(true)) => Some(true),
+                        (Some(false), Some(false)) => Some(false),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Read a doc comment block, its unknown commands are reported like
+    /// doxygen does
+    fn parse(&mut self, block: &CommentBlock) -> DocComment {
+        let doc = block.parse();
+        for (command, line) in &doc.unknown_commands {
+            self.builder.diagnostics.push(Diagnostic::new(
+                Rule::UnknownCommand,
+                format!("Found unknown command '{}'", command),
+                &self.location(*line),
+            ));
+        }
+        doc
+    }
+
+    /// Parse the pending block: a trailing block is returned, a structural
+    /// one handled and a leading one kept for the next declaration
+    fn flush(&mut self, comments: &mut Comments) -> Option<DocComment> {
+        let block = comments.block.take()?;
+        let doc = self.parse(&block);
+        if block.trailing {
+            return Some(doc);
+        }
+        if doc.is_structural() {
+            self.structural(doc, block.first_line());
+            return None;
+        }
+        match comments.leading.as_mut() {
+            Some(leading) => leading.append(doc),
+            None => comments.leading = Some(doc),
+        }
+        None
+    }
+
+    /// Add a comment to the pending blocks, returns the trailing block it
+    /// ends
+    fn comment(&mut self, comments: &mut Comments, block: CommentBlock) -> Option<DocComment> {
+        if let Some(pending) = comments.block.as_mut() {
+            if pending.can_merge(&block) {
+                pending.merge(block);
+                return None;
+            }
+        }
+        let trailing = self.flush(comments);
+        comments.block = Some(block);
+        trailing
+    }
+
+    /// File, group and member group blocks
+    fn structural(&mut self, doc: DocComment, line: i32) {
+        if doc.is_file {
+            set_description(&mut self.file.brief, &mut self.file.detailed, &doc);
+        }
+        let mut opened = None;
+        if let Some((name, title)) = &doc.group {
+            let location = self.location(line);
+            let group = self.builder.group(name);
+            if group.title.is_none() && !title.is_empty() {
+                group.title = Some(title.clone());
+            }
+            set_description(&mut group.brief, &mut group.detailed, &doc);
+            if group.location.is_none() {
+                group.location = location;
+            }
+            let id = group.id.clone();
+            for parent in &doc.ingroup {
+                self.builder
+                    .memberships
+                    .push((parent.clone(), id.clone(), Membership::Group));
+            }
+            opened = Some(name.clone());
+        }
+        for _ in 0..doc.closes_groups {
+            self.groups.pop();
+        }
+        if doc.opens_group {
+            self.groups.push(opened);
+        }
+    }
+
+    /// Groups and copied documentation of an entity
+    fn register(&mut self, doc: Option<&DocComment>, id: &str, membership: Membership) {
+        let ingroup = doc.map(|doc| doc.ingroup.clone()).unwrap_or_default();
+        let groups = match ingroup.is_empty() {
+            true => self
+                .groups
+                .iter()
+                .rev()
+                .flatten()
+                .take(1)
+                .cloned()
+                .collect(),
+            false => ingroup,
+        };
+        for group in groups {
+            self.builder
+                .memberships
+                .push((group, String::from(id), membership));
+        }
+        if let Some(doc) = doc.filter(|doc| !doc.copies.is_empty()) {
+            self.builder
+                .copies
+                .insert(String::from(id), doc.copies.clone());
+        }
+    }
+
+    fn list(&mut self, node: Node, scope: &str, class: &mut Option<ClassContext>) {
+        let mut nodes = Vec::new();
+        self.flatten(node, &mut nodes);
+        let mut comments = Comments::default();
+        let mut last = Last::None;
+        for child in nodes {
+            if child.kind() == "comment" {
+                let text = self.text(child);
+                if text.starts_with("/*Q_PROPERTY") {
+                    if let Some(trailing) = self.flush(&mut comments) {
+                        self.trailing(&trailing, last, class);
+                    }
+                    let doc = comments.leading.take();
+                    if let Some(context) = class.as_mut() {
+                        last = self.property(text, row(child), doc, context);
+                    }
+                    continue;
+                }
+                if let Some(block) = CommentBlock::new(text, row(child)) {
+                    if let Some(trailing) = self.comment(&mut comments, block) {
+                        self.trailing(&trailing, last, class);
+                    }
+                }
+                continue;
+            }
+            if let Some(trailing) = self.flush(&mut comments) {
+                self.trailing(&trailing, last, class);
+            }
+            let doc = comments.leading.take();
+            last = self.declaration(child, doc, scope, class);
+        }
+        if let Some(trailing) = self.flush(&mut comments) {
+            self.trailing(&trailing, last, class);
+        }
+    }
+
+    fn trailing(&mut self, doc: &DocComment, last: Last, class: &mut Option<ClassContext>) {
+        let id = match (last, class.as_mut()) {
+            (Last::Attribute(i), Some(context)) => {
+                let a = &mut context.class.attributes[i];
+                set_description(&mut a.brief, &mut a.detailed, doc);
+                a.id.clone()
+            }
+            (Last::Function(i), Some(context)) => {
+                let f = &mut context.class.functions[i];
+                set_function_description(f, doc);
+                f.id.clone()
+            }
+            (Last::Property(i), Some(context)) => {
+                let p = &mut context.class.properties[i];
+                set_description(&mut p.brief, &mut p.detailed, doc);
+                p.id.clone()
+            }
+            (Last::Macro(i), _) => {
+                let m = &mut self.file.macros[i];
+                set_description(&mut m.brief, &mut m.detailed, doc);
+                m.id.clone()
+            }
+            _ => return,
+        };
+        if !doc.copies.is_empty() {
+            self.builder.copies.insert(id, doc.copies.clone());
+        }
+    }
+
+    fn declaration(
+        &mut self,
+        node: Node,
+        doc: Option<DocComment>,
+        scope: &str,
+        class: &mut Option<ClassContext>,
+    ) -> Last {
+        match node.kind() {
+            "access_specifier" => {
+                if let Some(context) = class.as_mut() {
+                    context.access = access_from_str(self.text(node)).unwrap_or(Access::Public);
+                    context.kind = self
+                        .kinds
+                        .get(&node.start_position().row)
+                        .copied()
+                        .unwrap_or_default();
+                }
+                Last::None
+            }
+            "namespace_definition" => {
+                self.namespace(node, scope);
+                Last::None
+            }
+            "class_specifier" | "struct_specifier" => {
+                self.class(node, doc, scope, class, None);
+                Last::None
+            }
+            "enum_specifier" => {
+                self.enumeration(node, doc, scope, class, None);
+                Last::None
+            }
+            "template_declaration" => match named_children(node)
+                .into_iter()
+                .rfind(|child| child.kind() != "template_parameter_list")
+            {
+                Some(inner) => self.declaration(inner, doc, scope, class),
+                None => Last::None,
+            },
+            "field_declaration" | "declaration" | "function_definition" => {
+                self.member(node, doc, scope, class)
+            }
+            "type_definition" => {
+                let ty = match node.child_by_field_name("type") {
+                    Some(ty) => ty,
+                    None => return Last::None,
+                };
+                let name = node
+                    .child_by_field_name("declarator")
+                    .map(|name| String::from(self.text(name)));
+                match ty.kind() {
+                    "class_specifier" | "struct_specifier" => {
+                        self.class(ty, doc, scope, class, name)
+                    }
+                    "enum_specifier" => self.enumeration(ty, doc, scope, class, name),
+                    _ => {}
+                }
+                Last::None
+            }
+            "preproc_def" | "preproc_function_def" => self.define(node, doc),
+            _ => Last::None,
+        }
+    }
+
+    fn namespace(&mut self, node: Node, scope: &str) {
+        // Anonymous namespaces are not extracted
+        let name = match node.child_by_field_name("name") {
+            Some(name) => strip_template_arguments(self.text(name)),
+            None => return,
+        };
+        let qualified = join(scope, &name);
+        let id = format!("namespace{}", escape(&qualified));
+        self.builder.add_name(qualified.clone(), &id, "compound");
+        if let Some(body) = node.child_by_field_name("body") {
+            self.list(body, &qualified, &mut None);
+        }
+    }
+
+    fn class(
+        &mut self,
+        node: Node,
+        doc: Option<DocComment>,
+        scope: &str,
+        outer: &mut Option<ClassContext>,
+        typedef: Option<String>,
+    ) {
+        let body = match node.child_by_field_name("body") {
+            Some(body) => body,
+            None => return,
+        };
+        if outer.as_ref().is_some_and(|o| o.access == Access::Private) {
+            return;
+        }
+        let name_node = node.child_by_field_name("name");
+        let name = match (name_node, typedef) {
+            (Some(name), _) => compact(self.text(name)),
+            (None, Some(name)) => name,
+            (None, None) => return,
+        };
+
+        let mut class = Class::new();
+        class.is_struct = node.kind() == "struct_specifier";
+        class.name = join(scope, &name);
+        let prefix = match class.is_struct {
+            true => "struct",
+            false => "class",
+        };
+        class.id = self
+            .builder
+            .unique_id(format!("{}{}", prefix, escape(&class.name)));
+        class.location = self.location(row(name_node.unwrap_or(node)));
+        if let Some(doc) = &doc {
+            set_description(&mut class.brief, &mut class.detailed, doc);
+        }
+        self.register(doc.as_ref(), &class.id.clone(), Membership::Class);
+        self.builder
+            .add_name(class.name.clone(), &class.id, "compound");
+        if let Some(outer) = outer.as_mut() {
+            outer.class.inner_classes.push(class.name.clone());
+        }
+
+        let qualified = class.name.clone();
+        let access = match class.is_struct {
+            true => Access::Public,
+            false => Access::Private,
+        };
+        let mut context = Some(ClassContext {
+            class,
+            access,
+            kind: FunctionKind::Method,
+        });
+        self.list(body, &qualified, &mut context);
+        if let Some(context) = context {
+            self.builder.classes.push(context.class);
+        }
+    }
+
+    fn enumeration(
+        &mut self,
+        node: Node,
+        doc: Option<DocComment>,
+        scope: &str,
+        class: &mut Option<ClassContext>,
+        typedef: Option<String>,
+    ) {
+        let body = match node.child_by_field_name("body") {
+            Some(body) => body,
+            None => return,
+        };
+        if class.as_ref().is_some_and(|c| c.access == Access::Private) {
+            return;
+        }
+        let name_node = node.child_by_field_name("name");
+        let name = match (name_node, typedef) {
+            (Some(name), _) => compact(self.text(name)),
+            (None, Some(name)) => name,
+            (None, None) => {
+                self.builder.anonymous_enums += 1;
+                format!("@{}", self.builder.anonymous_enums - 1)
+            }
+        };
+
+        let mut enumerated = Enumerated::new();
+        enumerated.name = name.clone();
+        enumerated.full_name = join(scope, &name);
+        enumerated.is_strong = {
+            let mut cursor = node.walk();
+            let strong = node
+                .children(&mut cursor)
+                .any(|child| child.kind() == "class" || child.kind() == "struct");
+            strong
+        };
+        let compound = match class.as_ref() {
+            Some(context) => context.class.id.clone(),
+            None if !scope.is_empty() => format!("namespace{}", escape(scope)),
+            None => self.file.id.clone(),
+        };
+        enumerated.id = self.builder.member_id(&compound, &name);
+        enumerated.location = self.location(row(name_node.unwrap_or(node)));
+        if let Some(doc) = &doc {
+            set_description(&mut enumerated.brief, &mut enumerated.detailed, doc);
+        }
+        self.register(doc.as_ref(), &enumerated.id.clone(), Membership::Member);
+        self.builder
+            .add_name(enumerated.full_name.clone(), &enumerated.id, "member");
+
+        let mut comments = Comments::default();
+        let mut last: Option<usize> = None;
+        let describe = |values: &mut Vec<EnumValue>, last: Option<usize>, doc: &DocComment| {
+            if let Some(value) = last.and_then(|i| values.get_mut(i)) {
+                value.brief =
+                    non_empty(doc.brief.clone()).or_else(|| non_empty(doc.detailed.clone()));
+            }
+        };
+        for child in named_children(body) {
+            match child.kind() {
+                "comment" => {
+                    if let Some(block) = CommentBlock::new(self.text(child), row(child)) {
+                        if let Some(trailing) = self.comment(&mut comments, block) {
+                            describe(&mut enumerated.values, last, &trailing);
+                        }
+                    }
+                }
+                "enumerator" => {
+                    if let Some(trailing) = self.flush(&mut comments) {
+                        describe(&mut enumerated.values, last, &trailing);
+                    }
+                    let doc = comments.leading.take();
+                    let name = match child.child_by_field_name("name") {
+                        Some(name) => String::from(self.text(name)),
+                        None => continue,
+                    };
+                    let value = EnumValue {
+                        id: self.builder.member_id(&enumerated.id, &name),
+                        name,
+                        brief: None,
+                    };
+                    self.builder.add_name(
+                        format!("{}::{}", enumerated.full_name, value.name),
+                        &value.id,
+                        "member",
+                    );
+                    if !enumerated.is_strong {
+                        self.builder
+                            .add_name(join(scope, &value.name), &value.id, "member");
+                    }
+                    enumerated.values.push(value);
+                    last = Some(enumerated.values.len() - 1);
+                    if let Some(doc) = doc {
+                        describe(&mut enumerated.values, last, &doc);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(trailing) = self.flush(&mut comments) {
+            describe(&mut enumerated.values, last, &trailing);
+        }
+
+        match class.as_mut() {
+            Some(context) => context.class.enums.push(enumerated),
+            None => self.builder.enums.push(enumerated),
+        }
+    }
+
+    /// Type of a declaration, without its storage class and specifiers
+    fn type_text(&self, node: Node) -> String {
+        let mut parts = Vec::new();
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            loop {
+                let child = cursor.node();
+                match cursor.field_name() {
+                    Some("declarator") => break,
+                    Some("type") => parts.push(compact(self.text(child))),
+                    _ if child.kind() == "type_qualifier" => parts.push(compact(self.text(child))),
+                    _ => {}
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        parts.join(" ")
+    }
+
+    /// Type of a declarator, with its pointer and reference marks
+    fn declared_type(&self, node: Node, declarator_node: Node, declarator: &Declarator) -> String {
+        let ctype = self.type_text(node);
+        match (declarator.function_pointer, declarator.name) {
+            // void (*m_callback)(int) gives void (*)(int)
+            (true, Some(name)) => compact(&format!(
+                "{} {}",
+                ctype,
+                self.text(declarator_node).replace(self.text(name), "")
+            )),
+            _ if declarator.marks.is_empty() => ctype,
+            _ => format!("{} {}", ctype, declarator.marks),
+        }
+    }
+
+    fn is_static(&self, node: Node) -> bool {
+        let mut cursor = node.walk();
+        let found = node
+            .children(&mut cursor)
+            .any(|child| child.kind() == "storage_class_specifier" && self.text(child) == "static");
+        found
+    }
+
+    /// Name of a function as doxygen writes it
+    fn function_name(&self, node: Node) -> String {
+        match node.kind() {
+            "operator_cast" => {
+                let ty = node
+                    .child_by_field_name("type")
+                    .map(|ty| compact(self.text(ty)))
+                    .unwrap_or_default();
+                format!("operator {}", ty)
+            }
+            "operator_name" => {
+                let text = compact(self.text(node));
+                let rest = text.trim_start_matches("operator").trim();
+                match rest.starts_with(char::is_alphabetic) {
+                    true => format!("operator {}", rest),
+                    false => format!("operator{}", rest.replace(' ', "")),
+                }
+            }
+            "template_function" => node
+                .child_by_field_name("name")
+                .map(|name| String::from(self.text(name)))
+                .unwrap_or_default(),
+            _ => self.text(node).split_whitespace().collect(),
+        }
+    }
+
+    fn parameters(&self, list: Node) -> Vec<Parameter> {
+        let declarations: Vec<Node> = named_children(list)
+            .into_iter()
+            .filter(|child| {
+                child.kind() == "parameter_declaration"
+                    || child.kind() == "optional_parameter_declaration"
+            })
+            .collect();
+        let mut parameters = Vec::new();
+        for declaration in &declarations {
+            let (name, ctype) = match declaration.child_by_field_name("declarator") {
+                Some(declarator_node) => {
+                    let declarator = Declarator::read(declarator_node);
+                    let name = declarator.name.map(|name| String::from(self.text(name)));
+                    let ctype = self.declared_type(*declaration, declarator_node, &declarator);
+                    (name, ctype)
+                }
+                None => (None, self.type_text(*declaration)),
+            };
+            // f(void) has no parameter
+            if name.is_none() && ctype == "void" && declarations.len() == 1 {
+                break;
+            }
+            let mut parameter = Parameter::new();
+            parameter.name = name;
+            parameter.ctype = Some(ctype);
+            parameter.default_value =
+                declaration
+                    .child_by_field_name("default_value")
+                    .map(|value| {
+                        self.text(value)
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    });
+            parameters.push(parameter);
+        }
+        parameters
+    }
+
+    fn member(
+        &mut self,
+        node: Node,
+        doc: Option<DocComment>,
+        scope: &str,
+        class: &mut Option<ClassContext>,
+    ) -> Last {
+        let mut doc = doc;
+        if let Some(ty) = node.child_by_field_name("type") {
+            if ty.child_by_field_name("body").is_some() {
+                match ty.kind() {
+                    "class_specifier" | "struct_specifier" => {
+                        self.class(ty, doc.take(), scope, class, None)
+                    }
+                    "enum_specifier" => self.enumeration(ty, doc.take(), scope, class, None),
+                    _ => {}
+                }
+            }
+        }
+
+        let declarators: Vec<Node> = {
+            let mut cursor = node.walk();
+            let found = node
+                .children_by_field_name("declarator", &mut cursor)
+                .collect();
+            found
+        };
+        let mut last = Last::None;
+        for declarator_node in declarators {
+            let declarator = Declarator::read(declarator_node);
+            let name = match declarator.name {
+                Some(name) => name,
+                None => continue,
+            };
+            let context = match class.as_mut() {
+                Some(context) => context,
+                None => {
+                    self.free_declaration(&declarator, name, doc.take(), scope);
+                    continue;
+                }
+            };
+            if context.access == Access::Private {
+                last = Last::None;
+                continue;
+            }
+            let ctype = self.declared_type(node, declarator_node, &declarator);
+
+            if declarator.is_function() {
+                let mut function = Function::new();
+                function.name = self.function_name(name);
+                function.id = self.builder.member_id(&context.class.id, &function.name);
+                function.access = context.access;
+                function.kind = context.kind;
+                function.is_static = self.is_static(node);
+                function.is_const = declarator.is_const;
+                let ctype = ctype.trim();
+                function.ret_type = match ctype.is_empty() {
+                    true => None,
+                    false => Some(String::from(ctype)),
+                };
+                if let Some(list) = declarator.parameters {
+                    function.parameters = self.parameters(list);
+                }
+                function.location = self.location(row(name));
+                if let Some(doc) = &doc {
+                    set_function_description(&mut function, doc);
+                }
+                let id = function.id.clone();
+                self.builder.add_name(
+                    format!("{}::{}", context.class.name, function.name),
+                    &id,
+                    "member",
+                );
+                context.class.functions.push(function);
+                last = Last::Function(context.class.functions.len() - 1);
+                self.register(doc.as_ref(), &id, Membership::Member);
+            } else {
+                let mut attribute = Attribute::new();
+                attribute.name = String::from(self.text(name));
+                attribute.id = self.builder.member_id(&context.class.id, &attribute.name);
+                attribute.access = context.access;
+                attribute.is_static = self.is_static(node);
+                attribute.ctype = ctype;
+                attribute.location = self.location(row(name));
+                if let Some(doc) = &doc {
+                    set_description(&mut attribute.brief, &mut attribute.detailed, doc);
+                }
+                let id = attribute.id.clone();
+                self.builder.add_name(
+                    format!("{}::{}", context.class.name, attribute.name),
+                    &id,
+                    "member",
+                );
+                context.class.attributes.push(attribute);
+                last = Last::Attribute(context.class.attributes.len() - 1);
+                self.register(doc.as_ref(), &id, Membership::Member);
+            }
+        }
+        last
+    }
+
+    /// Declaration outside of a class: the functions are not part of the
+    /// model but can be referenced, the documented out-of-line definitions
+    /// of members are kept for their declaration
+    fn free_declaration(
+        &mut self,
+        declarator: &Declarator,
+        name: Node,
+        doc: Option<DocComment>,
+        scope: &str,
+    ) {
+        if !declarator.is_function() {
+            return;
+        }
+        let function_name = self.function_name(name);
+        if name.kind() == "qualified_identifier" {
+            if let Some(doc) = doc.filter(|doc| !doc.is_empty() || !doc.copies.is_empty()) {
+                let parameters = declarator
+                    .parameters
+                    .map_or(0, |list| self.parameters(list).len());
+                self.builder.definitions.push(Definition {
+                    scope: String::from(scope),
+                    name: function_name,
+                    parameters,
+                    doc,
+                });
+            }
+            return;
+        }
+        let compound = match scope.is_empty() {
+            true => self.file.id.clone(),
+            false => format!("namespace{}", escape(scope)),
+        };
+        let id = self.builder.member_id(&compound, &function_name);
+        self.builder
+            .add_name(join(scope, &function_name), &id, "member");
+    }
+
+    fn property(
+        &mut self,
+        text: &str,
+        line: i32,
+        doc: Option<DocComment>,
+        context: &mut ClassContext,
+    ) -> Last {
+        let declaration = text.trim_start_matches("/*").trim_end_matches("*/");
+        let arguments = match (declaration.find('('), declaration.rfind(')')) {
+            (Some(start), Some(end)) if start < end => &declaration[start + 1..end],
+            _ => return Last::None,
+        };
+        let words: Vec<&str> = arguments.split_whitespace().collect();
+        let end = words
+            .iter()
+            .position(|word| PROPERTY_KEYWORDS.contains(word))
+            .unwrap_or(words.len());
+        if end < 2 {
+            return Last::None;
+        }
+
+        let mut property = Property::new();
+        let name = words[end - 1];
+        let marks_length = name.len() - name.trim_start_matches(['*', '&']).len();
+        property.ctype = format!("{}{}", words[..end - 1].join(" "), &name[..marks_length]);
+        property.name = String::from(&name[marks_length..]);
+        let after = |keyword: &str| {
+            words
+                .iter()
+                .position(|word| *word == keyword)
+                .and_then(|i| words.get(i + 1))
+                .map(|word| String::from(*word))
+        };
+        property.read = after("READ");
+        property.write = after("WRITE");
+        property.read_macro(declaration);
+        property.id = self.builder.member_id(&context.class.id, &property.name);
+        property.location = self.location(line);
+        if let Some(doc) = &doc {
+            set_description(&mut property.brief, &mut property.detailed, doc);
+        }
+        self.builder.properties.push((
+            format!("{}::{}", context.class.name, property.name),
+            property.id.clone(),
+        ));
+        let id = property.id.clone();
+        context.class.properties.push(property);
+        self.register(doc.as_ref(), &id, Membership::Member);
+        Last::Property(context.class.properties.len() - 1)
+    }
+
+    fn define(&mut self, node: Node, doc: Option<DocComment>) -> Last {
+        let name_node = match node.child_by_field_name("name") {
+            Some(name) => name,
+            None => return Last::None,
+        };
+        let mut define = Macro::new();
+        define.name = String::from(self.text(name_node));
+        define.id = self.builder.member_id(&self.file.id.clone(), &define.name);
+        if let Some(list) = node.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            let parameters = list
+                .children(&mut cursor)
+                .filter(|child| child.kind() == "identifier" || child.kind() == "...")
+                .map(|child| String::from(self.text(child)))
+                .collect();
+            define.parameters = Some(parameters);
+        }
+        define.location = self.location(row(name_node));
+        if let Some(doc) = &doc {
+            set_description(&mut define.brief, &mut define.detailed, doc);
+        }
+        self.builder
+            .add_name(define.name.clone(), &define.id, "member");
+        let id = define.id.clone();
+        self.register(doc.as_ref(), &id, Membership::Member);
+
+        // The value ends with the trailing comment, as in
+        // #define SDK_VERSION 3 ///< Version
+        let trailing = node
+            .child_by_field_name("value")
+            .map(|value| (self.text(value), row(value)))
+            .and_then(|(value, line)| {
+                let start = value.find("///<").or_else(|| value.find("//!<"))?;
+                CommentBlock::new(&value[start..], line)
+            });
+        self.file.macros.push(define);
+        let last = Last::Macro(self.file.macros.len() - 1);
+        if let Some(block) = trailing {
+            let doc = self.parse(&block);
+            self.trailing(&doc, last, &mut None);
+        }
+        last
+    }
+}
+
+/// Names of the object-like macros defined by `sources`
+fn defined_macros(sources: &[(String, String)]) -> Vec<String> {
+    let define = Regex::new(r"(?m)^[ \t]*#[ \t]*define[ \t]+([A-Za-z_]\w*)(?:[ \t]|$)").unwrap();
+    let mut names: Vec<String> = Vec::new();
+    for (_, content) in sources {
+        for captures in define.captures_iter(content) {
+            if !names.iter().any(|name| name == &captures[1]) {
+                names.push(String::from(&captures[1]));
+            }
+        }
+    }
+    names
+}
+
+/// Read the `(path, content)` sources into `project`
+fn read_sources(
+    project: &mut Project,
+    sources: &[(String, String)],
+    options: &LoadOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<(), Error> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_cpp::LANGUAGE.into())
+        .map_err(Error::other)?;
+    let mut builder = Builder::new(options, &defined_macros(sources));
+    for (path, content) in sources {
+        builder.read_file(&mut parser, path, content, warnings);
+    }
+    builder.finish(project);
+    Ok(())
+}
+
+/// Read the C and C++ sources of `inputs` into `project`, the same way
+/// `Project::load` does with doxygen
+pub fn load(
+    project: &mut Project,
+    inputs: &[String],
+    options: &LoadOptions,
+) -> Result<Vec<ParseWarning>, Error> {
+    let files = source_files(inputs, &options.exclude)?;
+    let mut warnings = Vec::new();
+    let mut sources = Vec::new();
+    for path in files {
+        match fs::read(&path) {
+            Ok(bytes) => sources.push((path, String::from_utf8_lossy(&bytes).into_owned())),
+            Err(e) => warnings.push(ParseWarning::new(format!(
+                "Cannot read {}: {}, skipped",
+                path, e
+            ))),
+        }
+    }
+    read_sources(project, &sources, options, &mut warnings)?;
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str, options: &LoadOptions) -> Project {
+        let mut project = Project::new();
+        let sources = vec![(String::from("a.h"), String::from(source))];
+        let mut warnings = Vec::new();
+        read_sources(&mut project, &sources, options, &mut warnings).unwrap();
+        assert!(
+            warnings.is_empty(),
+            "{:?}",
+            warnings.iter().map(|w| &w.message).collect::<Vec<_>>()
+        );
+        project
+    }
+
+    fn class<'a>(project: &'a Project, name: &str) -> &'a Class {
+        project
+            .find_class(name)
+            .unwrap_or_else(|| panic!("class {} not found", name))
+    }
+
+    fn brief(description: &Option<Description>) -> &str {
+        description.as_ref().map_or("", |d| d.text.trim())
+    }
+
+    #[test]
+    fn compiled_preprocessor_branch_only() {
+        let source = "#define SDK_WIDGETS\n\
+            #ifdef SDK_WIDGETS\n\
+            class Widget {};\n\
+            #else\n\
+            class Fallback {};\n\
+            #endif\n\
+            #ifndef SDK_WIDGETS\n\
+            class Hidden {};\n\
+            #else\n\
+            class Shown {};\n\
+            #endif\n";
+        let project = parse(source, &LoadOptions::default());
+        let names: Vec<&str> = project.classes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Widget", "Shown"]);
+    }
+
+    #[test]
+    fn export_macros_are_removed() {
+        let source = "#define SDK_EXPORT\n\
+            /// A widget\n\
+            class SDK_EXPORT Widget {};\n\
+            class API Button final {};\n";
+        let options = LoadOptions {
+            export_macros: vec![String::from("API")],
+            ..Default::default()
+        };
+        let project = parse(source, &options);
+        assert_eq!(brief(&class(&project, "Widget").brief), "A widget");
+        assert!(project.find_class("Button").is_some());
+    }
+
+    #[test]
+    fn unknown_export_macros_are_removed() {
+        let source = "/// A widget\n\
+            class SDK_EXPORT Widget : public Base {};\n\
+            struct SDK_EXPORT Point {};\n\
+            class SDK_EXPORT Button final {};\n\
+            class SDK_EXPORT Label\n\
+            {};\n";
+        let project = parse(source, &LoadOptions::default());
+        assert_eq!(brief(&class(&project, "Widget").brief), "A widget");
+        assert!(project.find_class("Point").is_some());
+        assert!(project.find_class("Button").is_some());
+        assert!(project.find_class("Label").is_some());
+    }
+
+    #[test]
+    fn unknown_upper_case_names_are_kept() {
+        let source = "struct Shape {\n    struct POINT origin;\n};\n";
+        let project = parse(source, &LoadOptions::default());
+        let attributes = &project.objects.front().unwrap().attributes;
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].name, "origin");
+        assert_eq!(attributes[0].ctype, "struct POINT");
+    }
+
+    #[test]
+    fn qt_signals_and_slots() {
+        let source = "class Widget : public QObject {\n\
+            \x20   Q_OBJECT\n\
+            public:\n\
+            \x20   void show();\n\
+            signals:\n\
+            \x20   void shown();\n\
+            public slots:\n\
+            \x20   void hide();\n\
+            };\n";
+        let project = parse(source, &LoadOptions::default());
+        let kinds: Vec<(&str, FunctionKind, Access)> = class(&project, "Widget")
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.kind, f.access))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("show", FunctionKind::Method, Access::Public),
+                ("shown", FunctionKind::Signal, Access::Public),
+                ("hide", FunctionKind::Slot, Access::Public),
+            ]
+        );
+    }
+
+    #[test]
+    fn trailing_comments_document_the_previous_member() {
+        let source = "struct Size {\n\
+            \x20   int width;  ///< Width in pixels\n\
+            \x20   int height; ///< Height in pixels\n\
+            };\n\
+            enum Mode {\n\
+            \x20   Fast, ///< Quick\n\
+            \x20   Slow  ///< Precise\n\
+            };\n";
+        let project = parse(source, &LoadOptions::default());
+        let briefs: Vec<&str> = project
+            .objects
+            .front()
+            .unwrap()
+            .attributes
+            .iter()
+            .map(|a| brief(&a.brief))
+            .collect();
+        assert_eq!(briefs, ["Width in pixels", "Height in pixels"]);
+        let values: Vec<(&str, &str)> = project
+            .enums
+            .front()
+            .unwrap()
+            .values
+            .iter()
+            .map(|v| (v.name.as_str(), brief(&v.brief)))
+            .collect();
+        assert_eq!(values, [("Fast", "Quick"), ("Slow", "Precise")]);
+    }
+
+    #[test]
+    fn members_of_opened_groups() {
+        let source = "/// @defgroup shapes Shapes\n\
+            /// Geometric shapes\n\
+            \n\
+            /// @addtogroup shapes\n\
+            /// @{\n\
+            \n\
+            /// A circle\n\
+            class Circle {};\n\
+            /// Kinds of shapes\n\
+            enum Kind { Round };\n\
+            /// @}\n\
+            \n\
+            /// Not grouped\n\
+            class Line {};\n";
+        let project = parse(source, &LoadOptions::default());
+        let group = project.groups.front().unwrap();
+        assert_eq!(group.name, "shapes");
+        assert_eq!(group.title.as_deref(), Some("Shapes"));
+        assert_eq!(group.classes, [class(&project, "Circle").id.clone()]);
+        assert_eq!(group.members, [project.enums.front().unwrap().id.clone()]);
+        assert!(!group.classes.contains(&class(&project, "Line").id));
+    }
+
+    #[test]
+    fn const_methods_and_default_arguments() {
+        let source = "class Widget {\n\
+            public:\n\
+            \x20   int size() const;\n\
+            \x20   void resize(int width, bool fit = true);\n\
+            };\n";
+        let project = parse(source, &LoadOptions::default());
+        let functions = &class(&project, "Widget").functions;
+        assert!(functions[0].is_const);
+        assert!(!functions[1].is_const);
+        let defaults: Vec<Option<&str>> = functions[1]
+            .parameters
+            .iter()
+            .map(|p| p.default_value.as_deref())
+            .collect();
+        assert_eq!(defaults, [None, Some("true")]);
+    }
+}
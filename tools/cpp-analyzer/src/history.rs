@@ -0,0 +1,449 @@
+use crate::check::*;
+use crate::common::*;
+use crate::description::*;
+use crate::diagnostic::*;
+use crate::html::escape;
+use crate::project::*;
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Error, Write};
+use std::rc::Rc;
+
+/// Statistics of one analysis, one line of the JSON Lines history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStatistics {
+    /// Commit analysed, if the sources are in a git repository
+    pub commit: Option<String>,
+    /// RFC 3339 date of the analysis
+    pub timestamp: String,
+    /// Number of diagnostics reported, by rule id
+    pub diagnostics: BTreeMap<String, usize>,
+    /// Percentage of documented entities, by kind of entity
+    pub coverage: BTreeMap<String, f64>,
+}
+
+impl RunStatistics {
+    pub fn new(
+        commit: Option<String>,
+        diagnostics: &BTreeMap<Rule, usize>,
+        project: &Project,
+    ) -> RunStatistics {
+        RunStatistics {
+            commit,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            diagnostics: diagnostics
+                .iter()
+                .map(|(rule, count)| (String::from(rule.id()), *count))
+                .collect(),
+            coverage: coverage(project),
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.diagnostics.values().sum()
+    }
+}
+
+/// Documented and total number of entities of a kind
+#[derive(Debug, Default, Clone, Copy)]
+struct Count {
+    documented: usize,
+    total: usize,
+}
+
+impl Count {
+    fn add(&mut self, brief: &Option<Description>, detailed: &Option<Description>) {
+        self.total += 1;
+        if brief.is_some() || detailed.is_some() {
+            self.documented += 1;
+        }
+    }
+}
+
+/// Percentage of documented classes, enums, public and protected members
+/// and macros of the project, entities without any instance are left out
+pub fn coverage(project: &Project) -> BTreeMap<String, f64> {
+    let mut counts: BTreeMap<&str, Count> = BTreeMap::new();
+    for enumerated in &project.enums {
+        counts
+            .entry("enums")
+            .or_default()
+            .add(&enumerated.brief, &enumerated.detailed);
+    }
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        counts
+            .entry("classes")
+            .or_default()
+            .add(&class.brief, &class.detailed);
+        for enumerated in &class.enums {
+            counts
+                .entry("enums")
+                .or_default()
+                .add(&enumerated.brief, &enumerated.detailed);
+        }
+        for a in class
+            .attributes
+            .iter()
+            .filter(|a| a.access != Access::Private)
+        {
+            counts
+                .entry("attributes")
+                .or_default()
+                .add(&a.brief, &a.detailed);
+        }
+        for f in class
+            .functions
+            .iter()
+            .filter(|f| f.access != Access::Private)
+        {
+            counts
+                .entry("functions")
+                .or_default()
+                .add(&f.brief, &f.detailed);
+        }
+    }
+    for source in &project.files {
+        for define in &source.macros {
+            counts
+                .entry("macros")
+                .or_default()
+                .add(&define.brief, &define.detailed);
+        }
+    }
+
+    let all = counts.values().fold(Count::default(), |all, count| Count {
+        documented: all.documented + count.documented,
+        total: all.total + count.total,
+    });
+    counts.insert("total", all);
+
+    counts
+        .into_iter()
+        .filter(|(_, count)| count.total > 0)
+        .map(|(kind, count)| {
+            let percent = 100.0 * count.documented as f64 / count.total as f64;
+            (String::from(kind), (percent * 10.0).round() / 10.0)
+        })
+        .collect()
+}
+
+/// Count the diagnostics by rule before forwarding them, the counts stay
+/// readable once the writer is given to the analysis
+pub struct CountingErrorWriter {
+    pub counts: Rc<RefCell<BTreeMap<Rule, usize>>>,
+    pub inner: Box<dyn ErrorWriter>,
+}
+
+impl ErrorWriter for CountingErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), Error> {
+        *self.counts.borrow_mut().entry(diagnostic.rule).or_default() += 1;
+        self.inner.append(diagnostic)
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        self.inner.finish()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// Append the statistics of a run to the history file
+pub fn append(filename: &str, statistics: &RunStatistics) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename)?;
+    writeln!(file, "{}", serde_json::to_string(statistics)?)
+}
+
+/// Runs of the history file, oldest first
+pub fn read(filename: &str) -> Result<Vec<RunStatistics>, Error> {
+    let file = BufReader::new(File::open(filename)?);
+    let mut runs = Vec::new();
+    for (index, line) in file.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let run: RunStatistics = serde_json::from_str(&line)
+            .map_err(|e| Error::other(format!("{}:{}: {}", filename, index + 1, e)))?;
+        runs.push(run);
+    }
+    Ok(runs)
+}
+
+fn short_commit(run: &RunStatistics) -> &str {
+    match &run.commit {
+        Some(commit) => &commit[..commit.len().min(10)],
+        None => "-",
+    }
+}
+
+/// Write the diagnostic count and documentation coverage of each run, then
+/// the rules whose count changed between the first and last run
+pub fn write_trend(runs: &[RunStatistics], out: &mut dyn Write) -> Result<(), Error> {
+    writeln!(
+        out,
+        "{:<20} {:<10} {:>11} {:>9}",
+        "Date", "Commit", "Diagnostics", "Coverage"
+    )?;
+    for run in runs {
+        let coverage = match run.coverage.get("total") {
+            Some(percent) => format!("{:.1}%", percent),
+            None => String::from("-"),
+        };
+        writeln!(
+            out,
+            "{:<20} {:<10} {:>11} {:>9}",
+            run.timestamp,
+            short_commit(run),
+            run.total(),
+            coverage
+        )?;
+    }
+
+    if let (Some(first), Some(last)) = (runs.first(), runs.last()) {
+        let mut rules: Vec<&String> = first
+            .diagnostics
+            .keys()
+            .chain(last.diagnostics.keys())
+            .collect();
+        rules.sort();
+        rules.dedup();
+        for rule in rules {
+            let before = first.diagnostics.get(rule).copied().unwrap_or(0);
+            let after = last.diagnostics.get(rule).copied().unwrap_or(0);
+            if before != after {
+                writeln!(
+                    out,
+                    "{}: {} -> {} ({:+})",
+                    rule,
+                    before,
+                    after,
+                    after as i64 - before as i64
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 300.0;
+const MARGIN: f64 = 50.0;
+/// Room on the right of the charts for the legend
+const LEGEND: f64 = 220.0;
+
+/// SVG line chart of some series, one value per run
+fn write_chart(
+    file: &mut dyn Write,
+    title: &str,
+    runs: &[RunStatistics],
+    series: &[(String, Vec<Option<f64>>)],
+    max: f64,
+) -> Result<(), Error> {
+    let max = if max > 0.0 { max } else { 1.0 };
+    let x = |index: usize| {
+        let steps = runs.len().saturating_sub(1).max(1) as f64;
+        MARGIN + (WIDTH - MARGIN - LEGEND) * index as f64 / steps
+    };
+    let y = |value: f64| HEIGHT - MARGIN - (HEIGHT - 2.0 * MARGIN) * value / max;
+
+    writeln!(file, "<h2>{}</h2>", escape(title))?;
+    writeln!(
+        file,
+        "<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        WIDTH, HEIGHT
+    )?;
+    writeln!(
+        file,
+        "<line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#999\"/>\
+         <line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\" stroke=\"#999\"/>",
+        m = MARGIN,
+        b = HEIGHT - MARGIN,
+        r = WIDTH - LEGEND
+    )?;
+    writeln!(
+        file,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\">0</text>",
+        MARGIN - 5.0,
+        MARGIN + 5.0,
+        max,
+        MARGIN - 5.0,
+        HEIGHT - MARGIN + 5.0
+    )?;
+    for (index, run) in runs.iter().enumerate() {
+        writeln!(
+            file,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\">{}</text>",
+            x(index),
+            HEIGHT - MARGIN + 15.0,
+            escape(short_commit(run))
+        )?;
+    }
+    for (number, (name, values)) in series.iter().enumerate() {
+        let color = COLORS[number % COLORS.len()];
+        let points: Vec<String> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                value.map(|value| format!("{:.1},{:.1}", x(index), y(value)))
+            })
+            .collect();
+        writeln!(
+            file,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>",
+            color,
+            points.join(" ")
+        )?;
+        for (index, value) in values.iter().enumerate() {
+            if let Some(value) = value {
+                writeln!(
+                    file,
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"><title>{} {}: {}</title></circle>",
+                    x(index),
+                    y(*value),
+                    color,
+                    escape(&runs[index].timestamp),
+                    escape(name),
+                    value
+                )?;
+            }
+        }
+        writeln!(
+            file,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+            WIDTH - LEGEND + 10.0,
+            MARGIN + 15.0 * number as f64,
+            color,
+            escape(name)
+        )?;
+    }
+    writeln!(file, "</svg>")?;
+    Ok(())
+}
+
+/// Write the evolution of the diagnostics and of the coverage as a single
+/// HTML page with SVG charts
+pub fn write_html(runs: &[RunStatistics], filename: &str) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(filename)?);
+
+    let mut rules: Vec<&String> = runs.iter().flat_map(|run| run.diagnostics.keys()).collect();
+    rules.sort();
+    rules.dedup();
+    let mut kinds: Vec<&String> = runs.iter().flat_map(|run| run.coverage.keys()).collect();
+    kinds.sort();
+    kinds.dedup();
+
+    let mut diagnostics = vec![(
+        String::from("total"),
+        runs.iter()
+            .map(|run| Some(run.total() as f64))
+            .collect::<Vec<_>>(),
+    )];
+    // The rules with the most diagnostics, the others would be unreadable
+    let mut largest: Vec<(&String, usize)> = rules
+        .iter()
+        .map(|rule| {
+            let max = runs
+                .iter()
+                .filter_map(|run| run.diagnostics.get(*rule))
+                .max()
+                .copied()
+                .unwrap_or(0);
+            (*rule, max)
+        })
+        .collect();
+    largest.sort_by_key(|(_, max)| std::cmp::Reverse(*max));
+    for (rule, _) in largest.iter().take(COLORS.len() - 1) {
+        diagnostics.push((
+            (*rule).clone(),
+            runs.iter()
+                .map(|run| Some(run.diagnostics.get(*rule).copied().unwrap_or(0) as f64))
+                .collect(),
+        ));
+    }
+    let max = runs.iter().map(|run| run.total()).max().unwrap_or(0) as f64;
+
+    let coverage: Vec<(String, Vec<Option<f64>>)> = kinds
+        .iter()
+        .map(|kind| {
+            (
+                (*kind).clone(),
+                runs.iter()
+                    .map(|run| run.coverage.get(*kind).copied())
+                    .collect(),
+            )
+        })
+        .collect();
+
+    write!(file, "{}", HEADER)?;
+    writeln!(file, "<p>{} run(s)</p>", runs.len())?;
+    write_chart(&mut file, "Diagnostics", runs, &diagnostics, max)?;
+    write_chart(
+        &mut file,
+        "Documentation coverage (%)",
+        runs,
+        &coverage,
+        100.0,
+    )?;
+
+    writeln!(file, "<h2>Runs</h2>")?;
+    writeln!(file, "<table>")?;
+    write!(file, "<tr><th>Date</th><th>Commit</th><th>Diagnostics</th>")?;
+    for kind in &kinds {
+        write!(file, "<th>{} (%)</th>", escape(kind))?;
+    }
+    writeln!(file, "</tr>")?;
+    for run in runs {
+        write!(
+            file,
+            "<tr><td>{}</td><td>{}</td><td class=\"count\">{}</td>",
+            escape(&run.timestamp),
+            escape(run.commit.as_deref().unwrap_or_default()),
+            run.total()
+        )?;
+        for kind in &kinds {
+            match run.coverage.get(*kind) {
+                Some(percent) => write!(file, "<td class=\"count\">{:.1}</td>", percent)?,
+                None => write!(file, "<td></td>")?,
+            }
+        }
+        writeln!(file, "</tr>")?;
+    }
+    writeln!(file, "</table>")?;
+    write!(file, "{}", FOOTER)?;
+    file.flush()
+}
+
+const HEADER: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>cpp-analyzer trend</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 2px 6px; text-align: left; }
+th { background: #eee; }
+td.count { text-align: right; }
+svg text { font-size: 12px; }
+</style>
+</head>
+<body>
+<h1>cpp-analyzer trend</h1>
+";
+
+const FOOTER: &str = "</body>
+</html>
+";
@@ -0,0 +1,280 @@
+use crate::check::*;
+use crate::diagnostic::*;
+use crate::paths::*;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Write the diagnostics as a single HTML page, once all of them are known
+pub struct HtmlErrorWriter {
+    pub filename: String,
+    /// Diagnostics with their reported locations
+    pub diagnostics: Vec<Diagnostic>,
+    /// Mapping of the reported locations
+    pub mapping: PathMapping,
+    /// Lines of the files of the diagnostics, by reported file name
+    pub sources: HashMap<String, Vec<String>>,
+}
+
+impl HtmlErrorWriter {
+    pub fn new(filename: &str, mapping: &PathMapping) -> Result<HtmlErrorWriter, std::io::Error> {
+        // The page needs every diagnostic and is only written by finish,
+        // creating it here reports a bad file name before the analysis runs
+        File::create(filename)?;
+        Ok(HtmlErrorWriter {
+            filename: String::from(filename),
+            diagnostics: Vec::new(),
+            mapping: mapping.clone(),
+            sources: HashMap::new(),
+        })
+    }
+}
+
+/// `text` with the HTML special characters replaced by entities, for use in
+/// element content and quoted attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn directory(file: &str) -> String {
+    match Path::new(file).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            parent.to_string_lossy().replace('\\', "/")
+        }
+        _ => String::from("."),
+    }
+}
+
+fn write_summary(
+    file: &mut dyn Write,
+    title: &str,
+    counts: &BTreeMap<String, usize>,
+) -> Result<(), std::io::Error> {
+    writeln!(file, "<h2>{}</h2>", title)?;
+    writeln!(file, "<table class=\"summary\">")?;
+    for (name, count) in counts {
+        writeln!(
+            file,
+            "<tr><td>{}</td><td class=\"count\">{}</td></tr>",
+            escape(name),
+            count
+        )?;
+    }
+    writeln!(file, "</table>")?;
+    Ok(())
+}
+
+impl HtmlErrorWriter {
+    fn write(&self, file: &mut dyn Write) -> Result<(), std::io::Error> {
+        let mut by_rule: BTreeMap<String, usize> = BTreeMap::new();
+        let mut by_directory: BTreeMap<String, usize> = BTreeMap::new();
+        let mut by_owner: BTreeMap<String, usize> = BTreeMap::new();
+        let mut by_file: BTreeMap<&str, Vec<&Diagnostic>> = BTreeMap::new();
+        for diagnostic in &self.diagnostics {
+            *by_rule
+                .entry(format!(
+                    "{} - {}",
+                    diagnostic.rule,
+                    diagnostic.rule.description()
+                ))
+                .or_default() += 1;
+            let dir = match &diagnostic.location {
+                Some(loc) => directory(&loc.file),
+                None => String::from("(no location)"),
+            };
+            *by_directory.entry(dir).or_default() += 1;
+            for owner in &diagnostic.owners {
+                *by_owner.entry(owner.clone()).or_default() += 1;
+            }
+            if let Some(loc) = &diagnostic.location {
+                by_file
+                    .entry(loc.file.as_str())
+                    .or_default()
+                    .push(diagnostic);
+            }
+        }
+
+        write!(file, "{}", HEADER)?;
+        writeln!(
+            file,
+            "<p>{} diagnostic(s) in {} file(s)</p>",
+            self.diagnostics.len(),
+            by_file.len()
+        )?;
+        write_summary(file, "Rules", &by_rule)?;
+        write_summary(file, "Directories", &by_directory)?;
+        if !by_owner.is_empty() {
+            write_summary(file, "Owners", &by_owner)?;
+        }
+
+        writeln!(file, "<h2>Diagnostics</h2>")?;
+        writeln!(
+            file,
+            "<input id=\"filter\" type=\"search\" placeholder=\"Filter\" oninput=\"filterRows()\">"
+        )?;
+        writeln!(file, "<table id=\"diagnostics\">")?;
+        writeln!(
+            file,
+            "<thead><tr><th onclick=\"sortRows(0)\">Severity</th><th onclick=\"sortRows(1)\">Rule</th>\
+             <th onclick=\"sortRows(2)\">File</th><th onclick=\"sortRows(3)\">Line</th>\
+             <th onclick=\"sortRows(4)\">Message</th><th onclick=\"sortRows(5)\">Author</th>\
+             <th onclick=\"sortRows(6)\">Owners</th></tr></thead>"
+        )?;
+        writeln!(file, "<tbody>")?;
+        for diagnostic in &self.diagnostics {
+            let (filename, line) = match &diagnostic.location {
+                Some(loc) => (escape(&loc.file), loc.line.to_string()),
+                None => (String::new(), String::new()),
+            };
+            writeln!(
+                file,
+                "<tr class=\"{severity}\"><td>{severity}</td><td>{rule}</td><td>{filename}</td>\
+                 <td class=\"count\">{line}</td><td>{message}</td><td>{author}</td><td>{owners}</td></tr>",
+                severity = diagnostic.severity,
+                rule = diagnostic.rule,
+                filename = filename,
+                line = line,
+                message = escape(&diagnostic.message),
+                author = escape(diagnostic.author.as_deref().unwrap_or_default()),
+                owners = escape(&diagnostic.owners.join(" "))
+            )?;
+        }
+        writeln!(file, "</tbody>\n</table>")?;
+
+        writeln!(file, "<h2>Sources</h2>")?;
+        for (filename, mut diagnostics) in by_file {
+            diagnostics.sort_by_key(|diagnostic| diagnostic.location.as_ref().unwrap().line);
+            let lines = self
+                .sources
+                .get(filename)
+                .map_or(&[][..], |lines| &lines[..]);
+            writeln!(file, "<details>")?;
+            writeln!(
+                file,
+                "<summary>{} ({})</summary>",
+                escape(filename),
+                diagnostics.len()
+            )?;
+            writeln!(file, "<table class=\"source\">")?;
+            let mut previous_line = 0;
+            for diagnostic in diagnostics {
+                let line = diagnostic.location.as_ref().unwrap().line;
+                if line != previous_line {
+                    let code = match lines.get((line - 1).max(0) as usize) {
+                        Some(code) if line > 0 => escape(code),
+                        _ => String::new(),
+                    };
+                    writeln!(
+                        file,
+                        "<tr><td class=\"count\">{}</td><td><pre>{}</pre></td></tr>",
+                        line, code
+                    )?;
+                    previous_line = line;
+                }
+                writeln!(
+                    file,
+                    "<tr class=\"{severity}\"><td></td><td>{severity} {rule}: {message}</td></tr>",
+                    severity = diagnostic.severity,
+                    rule = diagnostic.rule,
+                    message = escape(&diagnostic.message)
+                )?;
+            }
+            writeln!(file, "</table>")?;
+            writeln!(file, "</details>")?;
+        }
+
+        write!(file, "{}", FOOTER)?;
+        Ok(())
+    }
+}
+
+impl ErrorWriter for HtmlErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        let mut diagnostic = diagnostic.clone();
+        if let Some(loc) = diagnostic.location.as_mut() {
+            let reported = self.mapping.apply(&loc.file);
+            // The analysed file name is the one that can be read
+            if !self.sources.contains_key(&reported) {
+                let lines = match fs::read_to_string(&loc.file) {
+                    Ok(text) => text.lines().map(String::from).collect(),
+                    Err(_) => Vec::new(),
+                };
+                self.sources.insert(reported.clone(), lines);
+            }
+            loc.file = reported;
+        }
+        self.diagnostics.push(diagnostic);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        let mut file = BufWriter::new(File::create(&self.filename)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+
+    fn name(&self) -> &'static str {
+        "HTML"
+    }
+}
+
+const HEADER: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>cpp-analyzer report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 2px 6px; text-align: left; vertical-align: top; }
+th { cursor: pointer; background: #eee; }
+td.count { text-align: right; }
+pre { margin: 0; }
+tr.error td { color: #b00; }
+tr.warning td { color: #a60; }
+tr.note td { color: #06a; }
+#filter { margin-bottom: 0.5em; width: 30em; }
+details { margin: 0.5em 0; }
+</style>
+<script>
+function filterRows() {
+  var text = document.getElementById('filter').value.toLowerCase();
+  var rows = document.querySelectorAll('#diagnostics tbody tr');
+  for (var i = 0; i < rows.length; i++) {
+    rows[i].style.display = rows[i].textContent.toLowerCase().indexOf(text) >= 0 ? '' : 'none';
+  }
+}
+function sortRows(column) {
+  var body = document.querySelector('#diagnostics tbody');
+  var rows = Array.prototype.slice.call(body.rows);
+  var ascending = body.getAttribute('data-sort') !== String(column);
+  rows.sort(function (a, b) {
+    var x = a.cells[column].textContent, y = b.cells[column].textContent;
+    var order = column === 3 ? (Number(x) - Number(y)) : x.localeCompare(y);
+    return ascending ? order : -order;
+  });
+  body.setAttribute('data-sort', ascending ? String(column) : '');
+  rows.forEach(function (row) { body.appendChild(row); });
+}
+</script>
+</head>
+<body>
+<h1>cpp-analyzer report</h1>
+";
+
+const FOOTER: &str = "</body>
+</html>
+";
@@ -0,0 +1,28 @@
+pub mod attribute;
+pub mod check;
+pub mod class;
+pub mod codeowners;
+pub mod comment;
+pub mod common;
+pub mod compile_commands;
+pub mod config;
+pub mod description;
+pub mod diagnostic;
+pub mod diff;
+pub mod doxygen;
+pub mod enumerated;
+pub mod file;
+pub mod function;
+pub mod git;
+pub mod group;
+pub mod header;
+pub mod history;
+pub mod html;
+pub mod markdown;
+pub mod metrics;
+pub mod paths;
+pub mod project;
+pub mod property;
+pub mod sarif;
+pub mod skeleton;
+pub mod vcxproj;
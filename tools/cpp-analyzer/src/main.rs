@@ -1,16 +1,25 @@
-mod attribute;
-mod check;
-mod class;
-mod common;
-mod doxygen;
-mod enumerated;
-mod function;
-mod project;
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use regex::Regex;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str::FromStr;
 
-use crate::check::*;
-use crate::project::*;
+use cpp_analyzer::check::*;
+use cpp_analyzer::codeowners::*;
+use cpp_analyzer::common::*;
+use cpp_analyzer::compile_commands::*;
+use cpp_analyzer::config::*;
+use cpp_analyzer::diff::*;
+use cpp_analyzer::git::*;
+use cpp_analyzer::history::{self, CountingErrorWriter, RunStatistics};
+use cpp_analyzer::markdown;
+use cpp_analyzer::metrics;
+use cpp_analyzer::paths::*;
+use cpp_analyzer::project::*;
+use cpp_analyzer::skeleton;
+use cpp_analyzer::vcxproj::*;
 
 fn main() {
     let matches = App::new("CPP Documentation Analyzer")
@@ -21,15 +30,81 @@ fn main() {
             Arg::with_name("output")
                 .long("output")
                 .short("o")
-                .help("Output format")
+                .value_name("format")
+                .help("Output: vs, csv:<file>, sarif:<file>, html:<file> or a .csv/.sarif/.html file, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("file")
+                .help("Configuration file, .cpp-analyzer.toml of the inputs or their parents by default")
+                .global(true)
                 .takes_value(true),
         )
         .arg(
             Arg::with_name("input")
-                .help("Input folders")
+                .help("Input folders, .vcxproj or .sln files")
                 .takes_value(true)
                 .multiple(true),
         )
+        .arg(
+            Arg::with_name("compile-commands")
+                .long("compile-commands")
+                .value_name("file")
+                .help("Analyse the files compiled in a compile_commands.json, with its defines and include paths")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("frontend")
+                .long("frontend")
+                .value_name("name")
+                .help("Read the sources with doxygen or with the built-in header parser")
+                .possible_values(&["doxygen", "builtin"])
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("export-macro")
+                .long("export-macro")
+                .value_name("macro")
+                .help("Export macro of the class declarations, for the built-in parser")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("source-root")
+                .long("source-root")
+                .value_name("folder")
+                .help("Write the locations relative to this folder")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("path-map")
+                .long("path-map")
+                .value_name("from=to")
+                .help("Replace a path prefix in the written locations, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("attribute")
+                .long("attribute")
+                .help("Give the git blame author of the line and the CODEOWNERS owners of the file of each diagnostic")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("per-owner")
+                .long("per-owner")
+                .value_name("folder")
+                .help("Write one CSV report per CODEOWNERS owner in the given folder")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("verbose")
                 .long("verbose")
@@ -37,27 +112,578 @@ fn main() {
                 .help("Verbose output")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("dump-model")
+                .long("dump-model")
+                .value_name("file")
+                .help("Write the parsed model to a JSON file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("markdown")
+                .long("markdown")
+                .value_name("folder")
+                .help("Generate a Markdown API reference in the given folder")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("since")
+                .long("since")
+                .value_name("git-ref")
+                .help("Only report errors located in lines changed since the given git reference")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("changed-files")
+                .long("changed-files")
+                .help("Only give the files changed since --since to doxygen")
+                .requires("since")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("header-case")
+                .long("header-case")
+                .value_name("case")
+                .help("Expected file name of class headers")
+                .possible_values(&["exact", "lower", "snake"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-classes-per-header")
+                .long("max-classes-per-header")
+                .value_name("count")
+                .help("Maximum number of public classes declared in a header")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("qt")
+                .long("qt")
+                .help("Check the documentation of Qt properties and signals")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("group-namespace")
+                .long("group-namespace")
+                .value_name("namespace")
+                .help("Namespace whose public classes must belong to exactly one doxygen group")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("deprecated-macro")
+                .long("deprecated-macro")
+                .value_name("macro")
+                .help("Macro marking deprecated declarations besides DEPRECATED")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("license")
+                .long("license")
+                .value_name("regex")
+                .help("Regular expression matching the license header expected at the top of headers")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("macro-prefix")
+                .long("macro-prefix")
+                .value_name("prefix")
+                .help("Prefix expected for the macros of the headers")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics")
+                .long("metrics")
+                .value_name("file")
+                .help("Write the class and function metrics to a CSV or JSON file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metric-limit")
+                .long("metric-limit")
+                .value_name("name=value")
+                .help("Limit of a metric, checked only when given: parameters, bool-parameters, overloads, public-methods or nesting-depth")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("history")
+                .long("history")
+                .value_name("file")
+                .help("Append the diagnostic counts and documentation coverage of the run to a JSON Lines file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("fix-skeletons")
+                .long("fix-skeletons")
+                .value_name("in-place|patch")
+                .help("Add doxygen comment blocks to undocumented entities, in the sources or as a patch file")
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Compare the public API of two source folders or model dumps")
+                .arg(
+                    Arg::with_name("old")
+                        .help("Old source folder or JSON model")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("new")
+                        .help("New source folder or JSON model")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("trend")
+                .about("Show the evolution of the runs recorded with --history")
+                .arg(
+                    Arg::with_name("history")
+                        .help("History file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("html")
+                        .long("html")
+                        .value_name("file")
+                        .help("Write the evolution as an HTML chart")
+                        .takes_value(true),
+                ),
+        )
         .get_matches();
 
-    let vec: Vec<String> = match matches.values_of("input") {
-        Some(val) => val.map(|x| String::from_str(x).unwrap()).collect(),
+    match matches.subcommand() {
+        ("diff", Some(sub_matches)) => diff(sub_matches),
+        ("trend", Some(sub_matches)) => trend(sub_matches),
+        _ => analyse(&matches),
+    }
+}
+
+fn read_config(path: Option<PathBuf>) -> Result<Config, std::io::Error> {
+    match path {
+        Some(path) => {
+            println!("Configuration: {}", path.display());
+            Config::read(&path)
+        }
+        None => Ok(Config::default()),
+    }
+}
+
+/// Where the sources of `project` were read from
+fn print_sources(project: &Project) {
+    if project.doxyfile.is_empty() {
+        println!("Parsed {} source file(s)", project.files.len());
+    } else {
+        println!("Temporary Doxyfile: {}", project.doxyfile);
+        println!("Output folder: {}", project.doxygen_output);
+    }
+}
+
+/// How the sources are read, from the configuration and the command line
+fn load_options(matches: &ArgMatches, config: &Config) -> LoadOptions {
+    let mut load_options = config.load_options();
+    if let Some(frontend) = matches.value_of("frontend") {
+        load_options.frontend = Frontend::from_name(frontend).unwrap();
+    }
+    if let Some(macros) = matches.values_of("export-macro") {
+        load_options.export_macros = macros.map(String::from).collect();
+    }
+    load_options
+}
+
+fn load_project(input: &str, load_options: &LoadOptions) -> Result<Project, std::io::Error> {
+    if input.to_lowercase().ends_with(".json") && Path::new(input).is_file() {
+        return Project::read_json(input);
+    }
+    let mut project = Project::new();
+    let warnings = project.load(vec![String::from(input)], load_options)?;
+    print_sources(&project);
+    for warning in warnings {
+        println!("Warning: {}", warning.message);
+    }
+    Ok(project)
+}
+
+fn diff(matches: &ArgMatches) {
+    let inputs = [
+        String::from(matches.value_of("old").unwrap()),
+        String::from(matches.value_of("new").unwrap()),
+    ];
+    let config_path = match matches.value_of("config") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Config::discover(&inputs),
+    };
+    let load_options = match read_config(config_path) {
+        Ok(config) => load_options(matches, &config),
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    let projects = load_project(&inputs[0], &load_options)
+        .and_then(|old| load_project(&inputs[1], &load_options).map(|new| (old, new)));
+    let (old, new) = match projects {
+        Ok(projects) => projects,
+        Err(e) => {
+            println!("Error: {:?}", e);
+            return;
+        }
+    };
+
+    let changes = diff_projects(&old, &new);
+    for change in &changes {
+        println!("{}", change);
+    }
+    println!(
+        "{} change(s), {} breaking",
+        changes.len(),
+        changes.iter().filter(|c| c.breaking).count()
+    );
+}
+
+fn trend(matches: &ArgMatches) {
+    let filename = matches.value_of("history").unwrap();
+    let runs = match history::read(filename) {
+        Ok(runs) => runs,
+        Err(e) => {
+            println!("Error: cannot read {}: {}", filename, e);
+            return;
+        }
+    };
+    if runs.is_empty() {
+        println!("No run in {}", filename);
+        return;
+    }
+
+    if let Err(e) = history::write_trend(&runs, &mut std::io::stdout()) {
+        println!("Error: {}", e);
+    }
+    if let Some(html) = matches.value_of("html") {
+        if let Err(e) = history::write_html(&runs, html) {
+            println!("Error: cannot write {}: {}", html, e);
+        }
+    }
+}
+
+fn analyse(matches: &ArgMatches) {
+    let inputs: Option<Vec<String>> = matches
+        .values_of("input")
+        .map(|val| val.map(|x| String::from_str(x).unwrap()).collect());
+
+    let config_path = match matches.value_of("config") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Config::discover(inputs.as_deref().unwrap_or(&[String::from(".")])),
+    };
+    let config = match read_config(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+
+    let explicit_inputs = inputs.is_some() || !config.input.is_empty();
+    let mut vec: Vec<String> = match inputs {
+        Some(inputs) => inputs,
+        None if !config.input.is_empty() => config.inputs(),
         None => vec![String::from(".")],
     };
 
-    let output_format: String = match matches.value_of("output") {
-        Some(val) => val.to_lowercase(),
-        None => String::from("output.csv"),
+    let mut warnings: Vec<ParseWarning> = Vec::new();
+    vec = match expand_project_files(&vec, &mut warnings) {
+        Ok(files) => files,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    for warning in warnings {
+        println!("Warning: {}", warning.message);
+    }
+
+    let mut load_options = load_options(matches, &config);
+    let compile_commands = match matches.value_of("compile-commands") {
+        Some(filename) => Some(String::from(filename)),
+        None => config.compile_commands(),
+    };
+    if let Some(filename) = compile_commands {
+        let mut warnings: Vec<ParseWarning> = Vec::new();
+        let database = match CompilationDatabase::read(&filename, &mut warnings) {
+            Ok(database) => database,
+            Err(e) => {
+                println!("Error: cannot read {}: {}", filename, e);
+                return;
+            }
+        };
+        for warning in warnings {
+            println!("Warning: {}", warning.message);
+        }
+        vec = if explicit_inputs {
+            database.files_in(&vec)
+        } else {
+            database.files.clone()
+        };
+        if vec.is_empty() {
+            println!("Error: no file of {} in the inputs", filename);
+            return;
+        }
+        load_options.defines = database.defines;
+        load_options.include_paths = database.include_paths;
+    }
+
+    let output_formats: Vec<String> = match matches.values_of("output") {
+        Some(val) => val.map(String::from).collect(),
+        None if !config.output.is_empty() => config.output.clone(),
+        None => vec![String::from("output.csv")],
     };
 
-    let mut output: Box<dyn ErrorWriter> = match output_format.as_str() {
-        "vs" => Box::new(VisualStudioErrorWriter {}),
-        _ => Box::new(CsvErrorWriter::new(output_format.as_str()).unwrap()),
+    let mut destinations: Vec<&str> = Vec::new();
+    for output_format in &output_formats {
+        if let (_, Some(destination)) = parse_writer_spec(output_format) {
+            if destinations.contains(&destination) {
+                println!(
+                    "Error: {} is the destination of several outputs",
+                    destination
+                );
+                return;
+            }
+            destinations.push(destination);
+        }
+    }
+
+    let mut prefixes: Vec<(String, String)> = Vec::new();
+    for argument in matches.values_of("path-map").into_iter().flatten() {
+        match PathMapping::parse_prefix(argument) {
+            Ok(prefix) => prefixes.push(prefix),
+            Err(e) => {
+                println!("Error: invalid --path-map: {}", e);
+                return;
+            }
+        }
+    }
+    if prefixes.is_empty() {
+        prefixes = config
+            .path_map
+            .iter()
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect();
+    }
+    let source_root = match matches.value_of("source-root") {
+        Some(root) => Some(String::from(root)),
+        None => config.source_root(),
+    };
+    let mapping = PathMapping::new(source_root.as_deref(), &prefixes);
+
+    let mut writers: Vec<Box<dyn ErrorWriter>> = Vec::new();
+    for output_format in &output_formats {
+        match create_error_writer(output_format, &mapping) {
+            Ok(writer) => writers.push(writer),
+            Err(e) => {
+                println!("Error: cannot create {}: {}", output_format, e);
+                return;
+            }
+        }
+    }
+    let per_owner = match matches.value_of("per-owner") {
+        Some(folder) => Some(String::from(folder)),
+        None => config.per_owner.clone(),
+    };
+    if let Some(folder) = &per_owner {
+        match PerOwnerErrorWriter::new(folder) {
+            Ok(writer) => writers.push(mapping.writer(Box::new(writer))),
+            Err(e) => {
+                println!("Error: cannot create {}: {}", folder, e);
+                return;
+            }
+        }
+    }
+    let mut output: Box<dyn ErrorWriter> = Box::new(CompositeErrorWriter { writers });
+    if matches.is_present("attribute") || config.attribute || per_owner.is_some() {
+        output = Box::new(AttributionErrorWriter {
+            attribution: Attribution::new(),
+            inner: output,
+        });
+    }
+
+    let history = match matches.value_of("history") {
+        Some(filename) => Some(String::from(filename)),
+        None => config.history.clone(),
+    };
+    // Only the changed files would be counted, unlike in the other runs
+    if history.is_some() && matches.is_present("changed-files") {
+        println!("Error: --history cannot be used with --changed-files");
+        return;
+    }
+
+    if let Some(reference) = matches.value_of("since") {
+        let changes = match ChangeSet::since(reference, &vec) {
+            Ok(changes) => changes,
+            Err(e) => {
+                println!("Error: {:?}", e);
+                return;
+            }
+        };
+        if matches.is_present("changed-files") {
+            vec = changes.files(&vec);
+            if vec.is_empty() {
+                println!("No source file changed since {}", reference);
+                println!("Done");
+                return;
+            }
+        }
+        output = Box::new(ChangedLinesErrorWriter {
+            changes,
+            inner: output,
+        });
+    }
+
+    // Inside the severities so that the rules turned off are not counted,
+    // outside --since so that every run counts the diagnostics of all lines
+    let counts: Rc<RefCell<BTreeMap<_, usize>>> = Rc::default();
+    if history.is_some() {
+        output = Box::new(CountingErrorWriter {
+            counts: counts.clone(),
+            inner: output,
+        });
+    }
+
+    match config.severities() {
+        Ok(severities) if severities.is_empty() => {}
+        Ok(severities) => {
+            output = Box::new(SeverityErrorWriter {
+                severities,
+                inner: output,
+            })
+        }
+        Err(e) => {
+            println!("Error: invalid configuration: {}", e);
+            return;
+        }
+    }
+
+    let mut options = CheckOptions {
+        qt: matches.is_present("qt") || config.qt,
+        ..Default::default()
+    };
+    options.group_namespaces = match matches.values_of("group-namespace") {
+        Some(val) => val.map(String::from).collect(),
+        None => config.group_namespaces.clone(),
+    };
+    options.deprecated_macros = match matches.values_of("deprecated-macro") {
+        Some(val) => val.map(String::from).collect(),
+        None => config.deprecated_macros.clone(),
+    };
+    options.macro_prefix = match matches.value_of("macro-prefix") {
+        Some(prefix) => Some(String::from(prefix)),
+        None => config.macro_prefix.clone(),
+    };
+    let license = match matches.value_of("license") {
+        Some(license) => Some(String::from(license)),
+        None => config.license.clone(),
+    };
+    if let Some(license) = license {
+        options.license = match Regex::new(&license) {
+            Ok(license) => Some(license),
+            Err(e) => {
+                println!("Error: invalid license pattern: {}", e);
+                return;
+            }
+        };
+    }
+    let header_case = match matches.value_of("header-case") {
+        Some(case) => Some(String::from(case)),
+        None => config.header_case.clone(),
+    };
+    if let Some(case) = header_case {
+        options.header_case = match HeaderCase::from_name(&case) {
+            Some(case) => Some(case),
+            None => {
+                println!("Error: unknown header case {}", case);
+                return;
+            }
+        };
+    }
+    for (name, value) in &config.metric_limits {
+        if let Err(e) = options.limits.set_limit(name, *value) {
+            println!("Error: invalid metric_limits: {}", e);
+            return;
+        }
+    }
+    for limit in matches.values_of("metric-limit").into_iter().flatten() {
+        if let Err(e) = options.limits.set(limit) {
+            println!("Error: invalid --metric-limit: {}", e);
+            return;
+        }
+    }
+    options.max_classes_per_header = config.max_classes_per_header;
+    if let Some(count) = matches.value_of("max-classes-per-header") {
+        options.max_classes_per_header = match count.parse::<usize>() {
+            Ok(count) => Some(count),
+            Err(e) => {
+                println!("Error: invalid --max-classes-per-header {}: {}", count, e);
+                return;
+            }
+        };
+    }
+
+    if matches.is_present("verbose") {
+        println!("Output: {}", output.name());
+    }
+
+    // Before the inputs are given to the analysis
+    let commit = match &history {
+        Some(_) => vec.first().and_then(|input| head_commit(input)),
+        None => None,
     };
 
     let mut project = Project::new();
-    match project.analyse(vec, output.as_mut()) {
+    let analysed = project.analyse(vec, &load_options, &options, output.as_mut());
+    match &analysed {
+        Ok(()) => print_sources(&project),
         Err(e) => println!("Error: {:?}", e),
-        _ => (),
+    }
+    if let Err(e) = output.finish() {
+        println!("Error: {:?}", e)
+    }
+
+    if let Some(target) = matches.value_of("fix-skeletons") {
+        match skeleton::fix(&project, target) {
+            Ok(count) => println!("{} comment block(s) added", count),
+            Err(e) => println!("Error: {:?}", e),
+        }
+    }
+
+    // The sources are no longer read, the model outputs give the reported
+    // file names like the diagnostics
+    mapping.apply_to_project(&mut project);
+
+    if let Some(filename) = matches.value_of("dump-model") {
+        if let Err(e) = project.write_json(filename) {
+            println!("Error: {:?}", e)
+        }
+    }
+
+    if let Some(folder) = matches.value_of("markdown") {
+        if let Err(e) = markdown::generate(&project, folder) {
+            println!("Error: {:?}", e)
+        }
+    }
+
+    if let Some(filename) = matches.value_of("metrics") {
+        if let Err(e) = metrics::write(&project, filename) {
+            println!("Error: {:?}", e)
+        }
+    }
+
+    // The counts of a failed analysis would look like an improvement
+    if let (Some(filename), Ok(())) = (&history, &analysed) {
+        let statistics = RunStatistics::new(commit, &counts.borrow(), &project);
+        if let Err(e) = history::append(filename, &statistics) {
+            println!("Error: cannot write {}: {}", filename, e)
+        }
     }
 
     println!("Done");
@@ -0,0 +1,358 @@
+use crate::class::*;
+use crate::common::*;
+use crate::description::*;
+use crate::function::*;
+use crate::project::*;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// File name of the page of a class, the characters of template
+/// specializations are escaped like in doxygen file names
+fn page_name(class: &Class) -> String {
+    let mut name = String::new();
+    for c in class.name.replace("::", "_").chars() {
+        match escaped_char(c) {
+            Some(code) if c != '_' => name.push_str(code),
+            _ => name.push(c),
+        }
+    }
+    format!("{}.md", name)
+}
+
+fn namespace(class: &Class) -> &str {
+    split_scope(&class.name).0
+}
+
+fn short_name(class: &Class) -> &str {
+    split_scope(&class.name).1
+}
+
+/// Text usable inside a table cell
+fn cell(description: &Option<Description>) -> String {
+    match description {
+        Some(description) => description
+            .text
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .replace('|', "\\|"),
+        None => String::new(),
+    }
+}
+
+fn direction(direction: &Option<Direction>) -> &'static str {
+    match direction {
+        Some(Direction::In) => "in",
+        Some(Direction::Out) => "out",
+        Some(Direction::InOut) => "in, out",
+        None => "",
+    }
+}
+
+fn inline_text(inlines: &[Inline]) -> String {
+    let mut text = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(chars) => text.push_str(chars),
+            Inline::Code(code) => text.push_str(&format!("`{}`", code)),
+            Inline::Ref(reference) => text.push_str(&reference.text),
+        }
+    }
+    text.split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .collect::<Vec<String>>()
+        .join("  \n")
+}
+
+fn section_title(kind: &str, title: &Option<String>) -> String {
+    match (kind, title) {
+        (_, Some(title)) => title.clone(),
+        ("see", None) => String::from("See also"),
+        ("return", None) => String::from("Returns"),
+        ("pre", None) => String::from("Precondition"),
+        ("post", None) => String::from("Postcondition"),
+        _ => {
+            let mut chars = kind.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+/// Render the description markup, `prefix` starts every line
+fn write_blocks(
+    file: &mut dyn Write,
+    blocks: &[Block],
+    prefix: &str,
+) -> Result<(), std::io::Error> {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) => {
+                writeln!(file, "{}{}\n", prefix, inline_text(inlines).trim())?
+            }
+            Block::Code(code) => {
+                writeln!(file, "{}```cpp", prefix)?;
+                for line in code.lines() {
+                    writeln!(file, "{}{}", prefix, line)?;
+                }
+                writeln!(file, "{}```\n", prefix)?;
+            }
+            Block::List { ordered, items } => {
+                for (i, item) in items.iter().enumerate() {
+                    let bullet = match ordered {
+                        true => format!("{}. ", i + 1),
+                        false => String::from("- "),
+                    };
+                    let mut content: Vec<u8> = Vec::new();
+                    write_blocks(&mut content, item, "")?;
+                    let content = String::from_utf8_lossy(&content);
+                    let indent = " ".repeat(bullet.len());
+                    for (j, line) in content.trim_end().lines().enumerate() {
+                        match (j, line.is_empty()) {
+                            (0, _) => writeln!(file, "{}{}{}", prefix, bullet, line)?,
+                            (_, true) => writeln!(file, "{}", prefix.trim_end())?,
+                            _ => writeln!(file, "{}{}{}", prefix, indent, line)?,
+                        }
+                    }
+                }
+                writeln!(file)?;
+            }
+            Block::Section {
+                kind,
+                title,
+                content,
+            } => {
+                writeln!(file, "{}> **{}**", prefix, section_title(kind, title))?;
+                writeln!(file, "{}>", prefix)?;
+                let mut quoted: Vec<u8> = Vec::new();
+                write_blocks(&mut quoted, content, "")?;
+                for line in String::from_utf8_lossy(&quoted).trim_end().lines() {
+                    writeln!(file, "{}> {}", prefix, line)?;
+                }
+                writeln!(file)?;
+            }
+            Block::Parameters { kind, items } => {
+                writeln!(file, "{}**{}**\n", prefix, section_title(kind, &None))?;
+                for item in items {
+                    let mut text = Description::from_blocks(item.description.clone()).text;
+                    text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+                    writeln!(file, "{}- `{}` {}", prefix, item.names.join(", "), text)?;
+                }
+                writeln!(file)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_description(
+    file: &mut dyn Write,
+    brief: &Option<Description>,
+    detailed: &Option<Description>,
+) -> Result<(), std::io::Error> {
+    for description in brief.iter().chain(detailed.iter()) {
+        // Descriptions of older model dumps have no markup
+        match description.blocks.is_empty() {
+            true => writeln!(file, "{}\n", description.text.trim())?,
+            false => write_blocks(file, &description.blocks, "")?,
+        }
+    }
+    Ok(())
+}
+
+/// Declaration of `function` with its qualifiers and default arguments
+fn function_signature(function: &Function) -> String {
+    let parameters: Vec<String> = function
+        .parameters
+        .iter()
+        .map(|p| {
+            let declaration = format!(
+                "{} {}",
+                p.ctype.as_deref().unwrap_or_default(),
+                p.name.as_deref().unwrap_or_default()
+            );
+            match &p.default_value {
+                Some(value) => format!("{} = {}", declaration.trim_end(), value),
+                None => declaration,
+            }
+        })
+        .collect();
+    format!(
+        "{}{}{}({}){}",
+        match function.is_static {
+            true => "static ",
+            false => "",
+        },
+        match &function.ret_type {
+            Some(ret_type) if !ret_type.is_empty() => format!("{} ", ret_type),
+            _ => String::new(),
+        },
+        function.name,
+        parameters.join(", "),
+        match function.is_const {
+            true => " const",
+            false => "",
+        }
+    )
+}
+
+fn write_function(file: &mut dyn Write, function: &Function) -> Result<(), std::io::Error> {
+    writeln!(file, "### {}\n", function.name)?;
+    writeln!(file, "```cpp\n{}\n```\n", function_signature(function))?;
+    write_description(file, &function.brief, &function.detailed)?;
+
+    if !function.parameters.is_empty() {
+        writeln!(file, "| Parameter | Direction | Description |")?;
+        writeln!(file, "|---|---|---|")?;
+        for p in &function.parameters {
+            writeln!(
+                file,
+                "| `{}` | {} | {} |",
+                p.name.as_deref().unwrap_or_default(),
+                direction(&p.direction),
+                cell(&p.description)
+            )?;
+        }
+        writeln!(file)?;
+    }
+    if let Some(ret_description) = &function.ret_description {
+        writeln!(file, "**Returns:** {}\n", ret_description.text.trim())?;
+    }
+    Ok(())
+}
+
+fn write_class(file: &mut dyn Write, class: &Class) -> Result<(), std::io::Error> {
+    writeln!(file, "# {} {}\n", class.object_type(), class.name)?;
+    write_description(file, &class.brief, &class.detailed)?;
+
+    if !class.enums.is_empty() {
+        writeln!(file, "## Enumerations\n")?;
+        for enumerated in &class.enums {
+            writeln!(file, "### {}\n", enumerated.name)?;
+            write_description(file, &enumerated.brief, &enumerated.detailed)?;
+            writeln!(file, "| Value | Description |")?;
+            writeln!(file, "|---|---|")?;
+            for value in &enumerated.values {
+                writeln!(file, "| `{}` | {} |", value.name, cell(&value.brief))?;
+            }
+            writeln!(file)?;
+        }
+    }
+
+    let functions: Vec<_> = class
+        .functions
+        .iter()
+        .filter(|f| f.access == Access::Public)
+        .collect();
+    if !functions.is_empty() {
+        writeln!(file, "## Methods\n")?;
+        for function in functions {
+            write_function(file, function)?;
+        }
+    }
+
+    let attributes: Vec<_> = class
+        .attributes
+        .iter()
+        .filter(|a| a.access == Access::Public)
+        .collect();
+    if !attributes.is_empty() {
+        writeln!(file, "## Attributes\n")?;
+        writeln!(file, "| Name | Type | Description |")?;
+        writeln!(file, "|---|---|---|")?;
+        for a in attributes {
+            writeln!(
+                file,
+                "| `{}` | `{}` | {} |",
+                a.name,
+                a.ctype,
+                cell(&a.brief.clone().or_else(|| a.detailed.clone()))
+            )?;
+        }
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+/// Write one Markdown page per class and an index grouped by namespace
+pub fn generate(project: &Project, folder: &str) -> Result<(), std::io::Error> {
+    fs::create_dir_all(folder)?;
+
+    let mut namespaces: BTreeMap<&str, Vec<&Class>> = BTreeMap::new();
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        let mut file = BufWriter::new(File::create(Path::new(folder).join(page_name(class)))?);
+        write_class(&mut file, class)?;
+        namespaces.entry(namespace(class)).or_default().push(class);
+    }
+
+    let mut index = BufWriter::new(File::create(Path::new(folder).join("index.md"))?);
+    writeln!(index, "# API reference\n")?;
+    for (namespace, mut classes) in namespaces {
+        classes.sort_by(|a, b| a.name.cmp(&b.name));
+        match namespace.is_empty() {
+            true => writeln!(index, "## Global namespace\n")?,
+            false => writeln!(index, "## {}\n", namespace)?,
+        }
+        for class in classes {
+            let line = format!(
+                "- [{}]({}) {}",
+                short_name(class),
+                page_name(class),
+                cell(&class.brief)
+            );
+            writeln!(index, "{}", line.trim_end())?;
+        }
+        writeln!(index)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str) -> Class {
+        let mut class = Class::new();
+        class.name = String::from(name);
+        class
+    }
+
+    #[test]
+    fn page_name_of_scoped_class() {
+        assert_eq!(page_name(&class("sdk::ui::Widget")), "sdk_ui_Widget.md");
+    }
+
+    #[test]
+    fn signature_with_qualifier_and_defaults() {
+        let mut function = Function::new();
+        function.name = String::from("resize");
+        function.ret_type = Some(String::from("int"));
+        function.is_const = true;
+        for (name, ctype, default_value) in
+            [("width", "int", None), ("mode", "Mode", Some("Mode::Fit"))]
+        {
+            let mut parameter = Parameter::new();
+            parameter.name = Some(String::from(name));
+            parameter.ctype = Some(String::from(ctype));
+            parameter.default_value = default_value.map(String::from);
+            function.parameters.push(parameter);
+        }
+        assert_eq!(
+            function_signature(&function),
+            "int resize(int width, Mode mode = Mode::Fit) const"
+        );
+    }
+
+    #[test]
+    fn page_name_of_template_specialization() {
+        assert_eq!(
+            page_name(&class("sdk::Box< int, 3 >")),
+            "sdk_Box_3_01int_00_013_01_4.md"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::class::*;
+use crate::common::*;
+use crate::function::*;
+use crate::project::*;
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Outlier thresholds, a metric above its limit is reported, the metrics
+/// without limit are not checked
+#[derive(Debug, Clone, Default)]
+pub struct MetricLimits {
+    pub parameters: Option<usize>,
+    pub bool_parameters: Option<usize>,
+    pub overloads: Option<usize>,
+    pub public_methods: Option<usize>,
+    pub nesting_depth: Option<usize>,
+}
+
+impl MetricLimits {
+    /// Set the limit of the metric `name`
+    pub fn set_limit(&mut self, name: &str, value: usize) -> Result<(), String> {
+        match name.trim() {
+            "parameters" => self.parameters = Some(value),
+            "bool-parameters" => self.bool_parameters = Some(value),
+            "overloads" => self.overloads = Some(value),
+            "public-methods" => self.public_methods = Some(value),
+            "nesting-depth" => self.nesting_depth = Some(value),
+            _ => return Err(format!("unknown metric {}", name)),
+        }
+        Ok(())
+    }
+
+    /// Set a limit from a `name=value` argument
+    pub fn set(&mut self, argument: &str) -> Result<(), String> {
+        let (name, value) = match argument.split_once('=') {
+            Some(pair) => pair,
+            None => return Err(format!("{} is not a name=value pair", argument)),
+        };
+        let value = value
+            .trim()
+            .parse::<usize>()
+            .map_err(|e| format!("invalid value for {}: {}", name, e))?;
+        self.set_limit(name, value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub access: Access,
+    pub parameters: usize,
+    pub bool_parameters: usize,
+    pub overloads: usize,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassMetrics {
+    pub name: String,
+    pub public_methods: usize,
+    pub nesting_depth: usize,
+    pub location: Option<Location>,
+    pub functions: Vec<FunctionMetrics>,
+}
+
+fn is_bool(ctype: &Option<String>) -> bool {
+    match ctype {
+        Some(ctype) => ctype
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == "bool"),
+        None => false,
+    }
+}
+
+fn function_metrics(class: &Class, function: &Function) -> FunctionMetrics {
+    let parameters: Vec<_> = function
+        .parameters
+        .iter()
+        .filter(|p| p.ctype.is_some())
+        .collect();
+    FunctionMetrics {
+        name: function.name.clone(),
+        access: function.access,
+        parameters: parameters.len(),
+        bool_parameters: parameters.iter().filter(|p| is_bool(&p.ctype)).count(),
+        // Private overloads are not part of the interface
+        overloads: class
+            .functions
+            .iter()
+            .filter(|f| f.name == function.name && f.access != Access::Private)
+            .count(),
+        location: function.location.clone(),
+    }
+}
+
+pub fn class_metrics(project: &Project, class: &Class) -> ClassMetrics {
+    let mut nesting_depth = 0;
+    let mut outer = project.outer_class(class);
+    while let Some(class) = outer {
+        nesting_depth += 1;
+        outer = project.outer_class(class);
+    }
+    ClassMetrics {
+        name: class.name.clone(),
+        public_methods: class
+            .functions
+            .iter()
+            .filter(|f| f.access == Access::Public && f.kind != FunctionKind::Signal)
+            .count(),
+        nesting_depth,
+        location: class.location.clone(),
+        functions: class
+            .functions
+            .iter()
+            .map(|f| function_metrics(class, f))
+            .collect(),
+    }
+}
+
+pub fn project_metrics(project: &Project) -> Vec<ClassMetrics> {
+    project
+        .classes
+        .iter()
+        .chain(project.objects.iter())
+        .map(|class| class_metrics(project, class))
+        .collect()
+}
+
+fn write_csv(metrics: &[ClassMetrics], file: &mut dyn Write) -> Result<(), std::io::Error> {
+    writeln!(
+        file,
+        "entity;name;access;parameters;bool_parameters;overloads;public_methods;nesting_depth;file;line;"
+    )?;
+    let location = |location: &Option<Location>| match location {
+        Some(loc) => format!("{};{}", loc.file, loc.line),
+        None => String::from(";"),
+    };
+    for class in metrics {
+        writeln!(
+            file,
+            "class;{};;;;;{};{};{};",
+            class.name,
+            class.public_methods,
+            class.nesting_depth,
+            location(&class.location)
+        )?;
+        for f in &class.functions {
+            writeln!(
+                file,
+                "function;{}::{};{:?};{};{};{};;;{};",
+                class.name,
+                f.name,
+                f.access,
+                f.parameters,
+                f.bool_parameters,
+                f.overloads,
+                location(&f.location)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the metrics as JSON if `filename` ends with .json, as CSV otherwise
+pub fn write(project: &Project, filename: &str) -> Result<(), std::io::Error> {
+    let metrics = project_metrics(project);
+    let mut file = BufWriter::new(File::create(filename)?);
+    if filename.to_lowercase().ends_with(".json") {
+        serde_json::to_writer_pretty(&mut file, &metrics)?;
+        writeln!(file)?;
+    } else {
+        write_csv(&metrics, &mut file)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(name: &str, access: Access) -> Function {
+        let mut function = Function::new();
+        function.name = String::from(name);
+        function.access = access;
+        function
+    }
+
+    #[test]
+    fn private_overloads_are_not_counted() {
+        let mut class = Class::new();
+        class.functions = vec![
+            method("set", Access::Public),
+            method("set", Access::Protected),
+            method("set", Access::Private),
+        ];
+        let metrics = class_metrics(&Project::new(), &class);
+        assert_eq!(metrics.functions[0].overloads, 2);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::check::*;
+use crate::diagnostic::*;
+use crate::project::*;
+
+use std::fs;
+
+/// Forward slashes, without the `\\?\` prefix of Windows canonical paths
+pub fn normalize_path(path: &str) -> String {
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+    path.replace('\\', "/")
+}
+
+/// Absolute Unix or Windows path, whatever the platform
+pub fn is_absolute_path(path: &str) -> bool {
+    let path = normalize_path(path);
+    let bytes = path.as_bytes();
+    path.starts_with('/') || (bytes.len() > 2 && bytes[1] == b':' && bytes[2] == b'/')
+}
+
+/// `path` without `prefix` if `prefix` is one of its parent folders
+fn strip_folder<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let prefix = prefix.trim_end_matches('/');
+    match path.strip_prefix(prefix) {
+        Some("") => Some(""),
+        Some(rest) => rest.strip_prefix('/'),
+        None => None,
+    }
+}
+
+/// Rewriting of the file names written in the reports
+#[derive(Debug, Clone, Default)]
+pub struct PathMapping {
+    /// Folder the locations are made relative to
+    pub source_root: Option<String>,
+    /// Prefixes replaced in the locations, longest first
+    pub prefixes: Vec<(String, String)>,
+}
+
+impl PathMapping {
+    pub fn new(source_root: Option<&str>, prefixes: &[(String, String)]) -> PathMapping {
+        let source_root = source_root.map(|root| match fs::canonicalize(root) {
+            Ok(root) => normalize_path(&root.to_string_lossy()),
+            Err(_) => normalize_path(root),
+        });
+        let mut prefixes: Vec<(String, String)> = prefixes
+            .iter()
+            .map(|(from, to)| (normalize_path(from), normalize_path(to)))
+            .collect();
+        prefixes.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        PathMapping {
+            source_root,
+            prefixes,
+        }
+    }
+
+    /// Parse a `from=to` prefix mapping
+    pub fn parse_prefix(argument: &str) -> Result<(String, String), String> {
+        match argument.split_once('=') {
+            Some((from, to)) if !from.is_empty() => Ok((String::from(from), String::from(to))),
+            _ => Err(format!("{} is not a from=to pair", argument)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source_root.is_none() && self.prefixes.is_empty()
+    }
+
+    /// File name to report for `file`: prefixes mapped first, then made
+    /// relative to the source root, with forward slashes
+    pub fn apply(&self, file: &str) -> String {
+        let mut file = normalize_path(file);
+        let mapped = self.prefixes.iter().find_map(|(from, to)| {
+            strip_folder(&file, from).map(|rest| match rest {
+                "" => String::from(to.trim_end_matches('/')),
+                rest => format!("{}/{}", to.trim_end_matches('/'), rest),
+            })
+        });
+        if let Some(mapped) = mapped {
+            file = mapped;
+        }
+        if let Some(root) = &self.source_root {
+            if let Some(rest) = strip_folder(&file, root) {
+                file = match rest {
+                    "" => String::from("."),
+                    rest => String::from(rest),
+                };
+            }
+        }
+        file
+    }
+
+    /// Give the locations of the model the reported file names, once the
+    /// sources are no longer read
+    pub fn apply_to_project(&self, project: &mut Project) {
+        if self.is_empty() {
+            return;
+        }
+        for location in project.locations_mut() {
+            location.file = self.apply(&location.file);
+        }
+    }
+
+    /// `inner` receiving the diagnostics with their locations mapped
+    pub fn writer(&self, inner: Box<dyn ErrorWriter>) -> Box<dyn ErrorWriter> {
+        match self.is_empty() {
+            true => inner,
+            false => Box::new(MappedPathErrorWriter {
+                mapping: self.clone(),
+                inner,
+            }),
+        }
+    }
+}
+
+/// Write the locations of the diagnostics as mapped by a `PathMapping`
+pub struct MappedPathErrorWriter {
+    pub mapping: PathMapping,
+    pub inner: Box<dyn ErrorWriter>,
+}
+
+impl ErrorWriter for MappedPathErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        let mut diagnostic = diagnostic.clone();
+        if let Some(loc) = diagnostic.location.as_mut() {
+            loc.file = self.mapping.apply(&loc.file);
+        }
+        self.inner.append(&diagnostic)
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        self.inner.finish()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
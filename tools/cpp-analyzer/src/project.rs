@@ -1,20 +1,93 @@
 use crate::check;
 use crate::check::*;
 use crate::class::*;
+use crate::common::*;
+use crate::diagnostic::*;
 use crate::doxygen;
+use crate::enumerated::*;
+use crate::file::*;
+use crate::group::*;
+use crate::header;
 
+use serde::{Deserialize, Serialize};
 use std::collections::LinkedList;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
 use std::str::FromStr;
 
-#[derive(Debug)]
+/// Parser reading the sources into the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frontend {
+    /// Run doxygen and read its XML output
+    #[default]
+    Doxygen,
+    /// Parse the headers directly, doxygen is not needed
+    Builtin,
+}
+
+impl Frontend {
+    pub fn from_name(name: &str) -> Option<Frontend> {
+        match name.to_lowercase().as_str() {
+            "doxygen" => Some(Frontend::Doxygen),
+            "builtin" => Some(Frontend::Builtin),
+            _ => None,
+        }
+    }
+}
+
+/// How the sources are given to doxygen
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub frontend: Frontend,
+    pub doxygen: String,
+    pub exclude: Vec<String>,
+    /// Macros given to the doxygen preprocessor, as `NAME` or `NAME=value`
+    pub defines: Vec<String>,
+    pub include_paths: Vec<String>,
+    /// Macros found between `class` and the class name, besides the defines
+    /// and the macros defined in the sources, for the built-in parser
+    pub export_macros: Vec<String>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            frontend: Frontend::default(),
+            doxygen: String::from(doxygen::DEFAULT_DOXYGEN),
+            exclude: Vec::new(),
+            defines: Vec::new(),
+            include_paths: Vec::new(),
+            export_macros: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Project {
     pub name: String,
     pub folder: String,
     pub doxygen_output: String,
+    /// Doxyfile generated to run doxygen, empty with the built-in parser
+    #[serde(skip)]
+    pub doxyfile: String,
     pub classes: LinkedList<Class>,
     pub objects: LinkedList<Class>,
-    pub enums: LinkedList<String>,
+    pub enums: LinkedList<Enumerated>,
+    #[serde(default)]
+    pub groups: LinkedList<Group>,
+    #[serde(default)]
+    pub files: LinkedList<SourceFile>,
+    /// Warnings written by doxygen, or by the built-in parser, when the
+    /// sources were loaded
+    #[serde(skip)]
+    pub doxygen_warnings: Vec<Diagnostic>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Project::new()
+    }
 }
 
 impl Project {
@@ -23,52 +96,227 @@ impl Project {
             name: String::new(),
             folder: String::new(),
             doxygen_output: String::new(),
+            doxyfile: String::new(),
             classes: LinkedList::new(),
             objects: LinkedList::new(),
             enums: LinkedList::new(),
+            groups: LinkedList::new(),
+            files: LinkedList::new(),
+            doxygen_warnings: Vec::new(),
         }
     }
 
     pub fn analyse(
         &mut self,
         folder: Vec<String>,
+        load_options: &LoadOptions,
+        options: &CheckOptions,
         error_writer: &mut dyn ErrorWriter,
     ) -> Result<(), std::io::Error> {
-        let (doxyfile, doxygen_output) = doxygen::generate_doxyfile(&folder, None).unwrap();
-        println!("Temporary Doxyfile: {}", &doxyfile);
+        let warnings = self.load(folder, load_options)?;
+        for warning in warnings {
+            error_writer.append(&Diagnostic::from(warning))?;
+        }
+        let mut recorder = RecordingErrorWriter::new(error_writer);
+        self.check(options, &mut recorder)?;
+        // Doxygen warnings already reported by a rule are dropped, the others
+        // keep the doxygen-warning id so that the exemptions and the severity
+        // of the rules they were classified in still apply
+        for diagnostic in &self.doxygen_warnings {
+            if recorder.contains(diagnostic) {
+                continue;
+            }
+            match diagnostic.rule {
+                Rule::DoxygenWarning | Rule::UnknownCommand => recorder.inner.append(diagnostic)?,
+                _ => {
+                    let mut diagnostic = diagnostic.clone();
+                    diagnostic.rule = Rule::DoxygenWarning;
+                    recorder.inner.append(&diagnostic)?;
+                }
+            }
+        }
+        Ok(())
+    }
 
-        doxygen::launch_doxygen(
-            doxyfile.as_str(),
-            "C:/Program Files/doxygen/bin/doxygen.exe",
-        )?;
+    pub fn load(
+        &mut self,
+        folder: Vec<String>,
+        options: &LoadOptions,
+    ) -> Result<Vec<ParseWarning>, std::io::Error> {
+        if options.frontend == Frontend::Builtin {
+            return header::load(self, &folder, options);
+        }
+        let (doxyfile, doxygen_output) = doxygen::generate_doxyfile(&folder, options, None)?;
+        doxygen::launch_doxygen(doxyfile.as_str(), options.doxygen.as_str())?;
+        self.doxygen_warnings = doxygen::read_warnings(&doxygen::warning_log(&doxyfile))?;
+        self.doxyfile = doxyfile;
+        self.doxygen_output = doxygen_output;
 
-        println!("Output folder: {}", &doxygen_output);
+        let mut paths = fs::read_dir(format!("{}xml/", &self.doxygen_output).as_str())?
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        paths.sort_by_key(|path| path.file_name());
 
-        for path in fs::read_dir(format!("{}xml/", &doxygen_output).as_str()).unwrap() {
-            let path = path?;
+        let mut warnings: Vec<ParseWarning> = Vec::new();
+        for path in paths {
             let filename = String::from_str(path.path().to_str().unwrap()).unwrap();
             if !filename.ends_with(".xml") {
                 continue;
             }
-            if path.file_name().to_str().unwrap().starts_with("class") {
-                match Class::read(filename.as_str(), false) {
-                    Ok(v) => match check::check_class(&v, error_writer) {
-                        Err(e) => println!("Error: {:?}", e),
-                        _ => (),
-                    },
-                    Err(e) => println!("Error: {:?}", e),
-                };
+            // Compounds are read by kind, file compounds are named after
+            // the file (Foo_8h.xml) and may look like any other compound
+            let kind = match read_compound_kind(filename.as_str()) {
+                Ok(Some(kind)) => kind,
+                Ok(None) => continue,
+                Err(e) => {
+                    warnings.push(ParseWarning::new(format!(
+                        "Cannot read {}: {}, skipped",
+                        filename, e
+                    )));
+                    continue;
+                }
+            };
+            if kind == "file" {
+                match SourceFile::read(filename.as_str(), &mut warnings) {
+                    Ok(file) => self.files.push_back(file),
+                    Err(e) => {
+                        warnings.push(ParseWarning::new(format!(
+                            "Cannot read {}: {}, skipped",
+                            filename, e
+                        )));
+                        continue;
+                    }
+                }
             }
-            if path.file_name().to_str().unwrap().starts_with("struct") {
-                match Class::read(filename.as_str(), true) {
-                    Ok(v) => match check::check_class(&v, error_writer) {
-                        Err(e) => println!("Error: {:?}", e),
-                        _ => (),
-                    },
-                    Err(e) => println!("Error: {:?}", e),
-                };
+            if kind == "namespace" || kind == "file" {
+                match Enumerated::read_compound(filename.as_str(), &mut warnings) {
+                    Ok(enums) => {
+                        for enumerated in enums {
+                            if !self.enums.iter().any(|e| {
+                                e.full_name == enumerated.full_name
+                                    && e.location == enumerated.location
+                            }) {
+                                self.enums.push_back(enumerated);
+                            }
+                        }
+                    }
+                    Err(e) => warnings.push(ParseWarning::new(format!(
+                        "Cannot read {}: {}, skipped",
+                        filename, e
+                    ))),
+                }
+                continue;
+            }
+            if kind == "group" {
+                match Group::read(filename.as_str(), &mut warnings) {
+                    Ok(group) => self.groups.push_back(group),
+                    Err(e) => warnings.push(ParseWarning::new(format!(
+                        "Cannot read {}: {}, skipped",
+                        filename, e
+                    ))),
+                }
+                continue;
             }
+            let is_struct = kind == "struct";
+            if !is_struct && kind != "class" {
+                continue;
+            }
+            match Class::read(filename.as_str(), is_struct, &mut warnings) {
+                Ok(v) if v.name.is_empty() => warnings.push(ParseWarning::new(format!(
+                    "Compound without name in {}, skipped",
+                    filename
+                ))),
+                Ok(v) if is_struct => self.objects.push_back(v),
+                Ok(v) => self.classes.push_back(v),
+                Err(e) => warnings.push(ParseWarning::new(format!(
+                    "Cannot read {}: {}, skipped",
+                    filename, e
+                ))),
+            };
         }
+        Ok(warnings)
+    }
+
+    pub fn check(
+        &self,
+        options: &CheckOptions,
+        error_writer: &mut dyn ErrorWriter,
+    ) -> Result<(), std::io::Error> {
+        for class in self.classes.iter().chain(self.objects.iter()) {
+            check::check_class(class, error_writer)?;
+            if options.qt {
+                check::check_qt_class(class, error_writer)?;
+            }
+        }
+        check::check_project(self, options, error_writer)
+    }
+
+    /// Locations of all the entities of the model
+    pub fn locations_mut(&mut self) -> Vec<&mut Location> {
+        let mut locations = Vec::new();
+        for class in self.classes.iter_mut().chain(self.objects.iter_mut()) {
+            locations.extend(class.location.as_mut());
+            locations.extend(
+                class
+                    .attributes
+                    .iter_mut()
+                    .filter_map(|a| a.location.as_mut()),
+            );
+            locations.extend(
+                class
+                    .functions
+                    .iter_mut()
+                    .filter_map(|f| f.location.as_mut()),
+            );
+            locations.extend(
+                class
+                    .properties
+                    .iter_mut()
+                    .filter_map(|p| p.location.as_mut()),
+            );
+            locations.extend(class.enums.iter_mut().filter_map(|e| e.location.as_mut()));
+        }
+        locations.extend(self.enums.iter_mut().filter_map(|e| e.location.as_mut()));
+        locations.extend(self.groups.iter_mut().filter_map(|g| g.location.as_mut()));
+        for file in self.files.iter_mut() {
+            locations.extend(file.location.as_mut());
+            locations.extend(file.macros.iter_mut().filter_map(|m| m.location.as_mut()));
+        }
+        locations
+    }
+
+    pub fn find_class(&self, name: &str) -> Option<&Class> {
+        self.classes
+            .iter()
+            .chain(self.objects.iter())
+            .find(|class| class.name == name)
+    }
+
+    /// Class declaring `class` as one of its inner classes
+    pub fn outer_class(&self, class: &Class) -> Option<&Class> {
+        self.classes
+            .iter()
+            .chain(self.objects.iter())
+            .find(|outer| outer.inner_classes.contains(&class.name))
+    }
+
+    /// Groups listing the class or member `id`
+    pub fn groups_of(&self, id: &str) -> Vec<&Group> {
+        self.groups
+            .iter()
+            .filter(|group| {
+                group.classes.iter().any(|c| c == id) || group.members.iter().any(|m| m == id)
+            })
+            .collect()
+    }
+
+    pub fn read_json(filename: &str) -> Result<Project, std::io::Error> {
+        let file = BufReader::new(File::open(filename)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn write_json(&self, filename: &str) -> Result<(), std::io::Error> {
+        let file = BufWriter::new(File::create(filename)?);
+        serde_json::to_writer_pretty(file, self)?;
         Ok(())
     }
 }
@@ -0,0 +1,156 @@
+use crate::common::*;
+use crate::description::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use xml::{
+    attribute::OwnedAttribute,
+    reader::{EventReader, XmlEvent},
+};
+
+/// Qt property declared with `Q_PROPERTY`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Property {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub ctype: String,
+    pub brief: Option<Description>,
+    pub detailed: Option<Description>,
+    pub location: Option<Location>,
+    pub read: Option<String>,
+    pub write: Option<String>,
+    pub notify: Option<String>,
+    pub is_constant: bool,
+}
+
+impl Default for Property {
+    fn default() -> Self {
+        Property::new()
+    }
+}
+
+/// Arguments of the macro call starting `text`, up to the parenthesis
+/// closing the first one, the whole rest of `text` if it is not closed
+fn macro_arguments(text: &str) -> &str {
+    let start = match text.find('(') {
+        Some(start) => start + 1,
+        None => return "",
+    };
+    let mut depth = 0;
+    for (i, c) in text[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return &text[start..start + i],
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    &text[start..]
+}
+
+impl Property {
+    pub fn new() -> Property {
+        Property {
+            id: String::new(),
+            name: String::new(),
+            ctype: String::new(),
+            brief: Option::None,
+            detailed: Option::None,
+            location: Option::None,
+            read: Option::None,
+            write: Option::None,
+            notify: Option::None,
+            is_constant: false,
+        }
+    }
+
+    /// Read the NOTIFY and CONSTANT parts of a `Q_PROPERTY(...)` declaration
+    pub fn read_macro(&mut self, declaration: &str) {
+        let arguments = match declaration.find("Q_PROPERTY") {
+            Some(start) => macro_arguments(&declaration[start..]),
+            None => return,
+        };
+        let mut words = arguments.split(|c: char| c.is_whitespace() || c == '(' || c == ')');
+        while let Some(word) = words.next() {
+            match word {
+                "NOTIFY" => {
+                    self.notify = words.find(|w| !w.is_empty()).map(String::from);
+                }
+                "CONSTANT" => self.is_constant = true,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn read(
+        parser: &mut EventReader<BufReader<File>>,
+        xml_attributes: &[OwnedAttribute],
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Property, std::io::Error> {
+        let mut property = Property::new();
+        property.id = read_xml_attribute(xml_attributes, "id").unwrap_or_default();
+
+        loop {
+            match parser.next() {
+                Ok(XmlEvent::StartElement {
+                    ref name,
+                    ref attributes,
+                    ..
+                }) => match name.local_name.as_str() {
+                    "type" => property.ctype = read_characters_only(parser)?.unwrap_or_default(),
+                    "name" => property.name = read_characters_only(parser)?.unwrap_or_default(),
+                    "read" => property.read = read_characters_only(parser)?,
+                    "write" => property.write = read_characters_only(parser)?,
+                    "briefdescription" => property.brief = read_description(parser, warnings)?,
+                    "detaileddescription" => {
+                        property.detailed = read_description(parser, warnings)?
+                    }
+                    "location" => property.location = Some(Location::read(attributes)),
+                    _ => {}
+                },
+                Ok(XmlEvent::EndElement { ref name }) if name.local_name == "memberdef" => {
+                    break;
+                }
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        e.clone(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(property)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(declaration: &str) -> Property {
+        let mut property = Property::new();
+        property.read_macro(declaration);
+        property
+    }
+
+    #[test]
+    fn notify_after_a_type_with_parentheses() {
+        let property = read(
+            "Q_PROPERTY(std::function<void(int)> callback READ callback NOTIFY callbackChanged)",
+        );
+        assert_eq!(property.notify.as_deref(), Some("callbackChanged"));
+        assert!(!property.is_constant);
+    }
+
+    #[test]
+    fn parts_after_the_macro_are_ignored() {
+        let property = read("Q_PROPERTY(int size READ size) void f(CONSTANT);");
+        assert_eq!(property.notify, None);
+        assert!(!property.is_constant);
+        assert!(read("Q_PROPERTY(int size READ size CONSTANT)").is_constant);
+    }
+}
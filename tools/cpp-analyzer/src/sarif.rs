@@ -0,0 +1,183 @@
+use crate::check::*;
+use crate::diagnostic::*;
+use crate::paths::*;
+
+use serde_json::{json, Value};
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+
+/// Write the diagnostics as a SARIF 2.1.0 log, once all of them are known
+pub struct SarifErrorWriter {
+    pub filename: String,
+    /// Folder the relative locations are resolved from, `%SRCROOT%`
+    pub source_root: String,
+    pub results: Vec<Value>,
+}
+
+impl SarifErrorWriter {
+    /// `source_root` defaults to the current directory, which the relative
+    /// locations are relative to without --source-root
+    pub fn new(
+        filename: &str,
+        source_root: Option<&str>,
+    ) -> Result<SarifErrorWriter, std::io::Error> {
+        // The log is only written by finish, a bad file name would otherwise
+        // be found after the whole analysis
+        File::create(filename)?;
+        let source_root = match source_root {
+            Some(root) => String::from(root),
+            None => env::current_dir()?.to_string_lossy().into_owned(),
+        };
+        Ok(SarifErrorWriter {
+            filename: String::from(filename),
+            source_root,
+            results: Vec::new(),
+        })
+    }
+}
+
+/// `path` with forward slashes and the reserved characters percent-encoded
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::new();
+    for byte in normalize_path(path).bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// `file:///` URI of an absolute path
+fn file_uri(path: &str) -> String {
+    format!("file:///{}", encode_path(path).trim_start_matches('/'))
+}
+
+/// URI of a reported file, `file:///` for absolute paths and relative to
+/// `%SRCROOT%` otherwise
+fn artifact_location(file: &str) -> Value {
+    if is_absolute_path(file) {
+        json!({ "uri": file_uri(file) })
+    } else {
+        json!({ "uri": encode_path(file), "uriBaseId": "%SRCROOT%" })
+    }
+}
+
+/// `originalUriBaseIds` of the run, giving `%SRCROOT%` as a folder URI
+fn uri_base_ids(source_root: &str) -> Value {
+    let uri = file_uri(source_root);
+    let uri = match uri.ends_with('/') {
+        true => uri,
+        false => format!("{}/", uri),
+    };
+    json!({ "%SRCROOT%": { "uri": uri } })
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Off => "none",
+        Severity::Note => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+impl ErrorWriter for SarifErrorWriter {
+    fn append(&mut self, diagnostic: &Diagnostic) -> Result<(), std::io::Error> {
+        let mut result = json!({
+            "ruleId": diagnostic.rule.id(),
+            "level": level(diagnostic.severity),
+            "message": { "text": diagnostic.message },
+        });
+        if let Some(loc) = &diagnostic.location {
+            result["locations"] = json!([{
+                "physicalLocation": {
+                    "artifactLocation": artifact_location(&loc.file),
+                    "region": { "startLine": loc.line.max(1) },
+                }
+            }]);
+        }
+        if let Some(author) = &diagnostic.author {
+            result["properties"]["author"] = json!(author);
+        }
+        if !diagnostic.owners.is_empty() {
+            result["properties"]["owners"] = json!(diagnostic.owners);
+        }
+        self.results.push(result);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        let rules: Vec<Value> = Rule::ALL
+            .iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id(),
+                    "shortDescription": { "text": rule.description() },
+                })
+            })
+            .collect();
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "cpp-analyzer",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "originalUriBaseIds": uri_base_ids(&self.source_root),
+                "results": self.results,
+            }]
+        });
+        let file = BufWriter::new(File::create(&self.filename)?);
+        serde_json::to_writer_pretty(file, &log)?;
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "SARIF"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_paths_are_file_uris() {
+        assert_eq!(
+            artifact_location("C:\\src\\a.h"),
+            json!({ "uri": "file:///C:/src/a.h" })
+        );
+        assert_eq!(
+            artifact_location("/home/x/a.h"),
+            json!({ "uri": "file:///home/x/a.h" })
+        );
+    }
+
+    #[test]
+    fn source_root_is_a_folder_uri() {
+        assert_eq!(
+            uri_base_ids("C:\\dev\\my sdk"),
+            json!({ "%SRCROOT%": { "uri": "file:///C:/dev/my%20sdk/" } })
+        );
+        assert_eq!(
+            uri_base_ids("/home/x/"),
+            json!({ "%SRCROOT%": { "uri": "file:///home/x/" } })
+        );
+    }
+
+    #[test]
+    fn relative_paths_use_the_source_root() {
+        assert_eq!(
+            artifact_location("include/my widget.h"),
+            json!({ "uri": "include/my%20widget.h", "uriBaseId": "%SRCROOT%" })
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use crate::common::*;
+use crate::function::*;
+use crate::project::*;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const CONTEXT: usize = 3;
+
+/// Comment blocks to insert in a source file
+pub struct FileSkeletons {
+    pub file: String,
+    lines: Vec<String>,
+    line_ending: &'static str,
+    insertions: BTreeMap<usize, Vec<String>>,
+}
+
+/// Type without its template arguments
+fn without_template_arguments(ctype: &str) -> String {
+    let mut depth = 0;
+    let mut text = String::new();
+    for c in ctype.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            _ if depth == 0 => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn has_const(ctype: &str) -> bool {
+    ctype
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|word| word == "const")
+}
+
+/// `out` for the pointers and references to a non-const type, given the
+/// qualifier of the pointee rather than the one of the pointer itself
+fn parameter_direction(ctype: &Option<String>) -> &'static str {
+    let ctype = without_template_arguments(ctype.as_deref().unwrap_or_default());
+    let mut ctype = ctype.trim();
+    while let Some(rest) = ctype.strip_suffix("const") {
+        if rest.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+            break;
+        }
+        ctype = rest.trim_end();
+    }
+    if ctype.ends_with("&&") {
+        return "in";
+    }
+    let pointee = match ctype.strip_suffix(['*', '&']) {
+        Some(pointee) => pointee,
+        None => return "in",
+    };
+    let pointee = match pointee.rfind(['*', '&']) {
+        Some(index) => &pointee[index + 1..],
+        None => pointee,
+    };
+    match has_const(pointee) {
+        true => "in",
+        false => "out",
+    }
+}
+
+fn function_skeleton(function: &Function) -> Vec<String> {
+    let mut lines = vec![String::from("@brief TODO")];
+    for p in &function.parameters {
+        if let Some(name) = &p.name {
+            lines.push(format!(
+                "@param[{}] {} TODO",
+                parameter_direction(&p.ctype),
+                name
+            ));
+        }
+    }
+    if let Some(ret_type) = &function.ret_type {
+        if !ret_type.is_empty() && ret_type.trim() != "void" {
+            lines.push(String::from("@return TODO"));
+        }
+    }
+    lines
+}
+
+fn is_anonymous(name: &str) -> bool {
+    let (_, short) = split_scope(name);
+    short.is_empty() || short.starts_with('@')
+}
+
+/// Undocumented entities with the content of their comment block
+fn undocumented(project: &Project) -> Vec<(&Location, Vec<String>)> {
+    let brief = || vec![String::from("@brief TODO")];
+    let mut entities = Vec::new();
+    for enumerated in &project.enums {
+        if let (None, Some(location)) = (&enumerated.brief, &enumerated.location) {
+            if !is_anonymous(&enumerated.full_name) {
+                entities.push((location, brief()));
+            }
+        }
+    }
+    for class in project.classes.iter().chain(project.objects.iter()) {
+        if let Some(location) = &class.location {
+            if class.brief.is_none() && class.detailed.is_none() && !is_anonymous(&class.name) {
+                entities.push((location, brief()));
+            }
+        }
+        for enumerated in &class.enums {
+            if let (None, Some(location)) = (&enumerated.brief, &enumerated.location) {
+                if !is_anonymous(&enumerated.name) {
+                    entities.push((location, brief()));
+                }
+            }
+        }
+        for a in &class.attributes {
+            if let Some(location) = &a.location {
+                if a.brief.is_none() && a.detailed.is_none() && !is_anonymous(&a.name) {
+                    entities.push((location, brief()));
+                }
+            }
+        }
+        for f in &class.functions {
+            if let Some(location) = &f.location {
+                if f.brief.is_none() && f.detailed.is_none() {
+                    entities.push((location, function_skeleton(f)));
+                }
+            }
+        }
+    }
+    entities
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("//") || line.ends_with("*/")
+}
+
+/// Whether a comment is found above the line `index`, doxygen attaches it
+/// to the declaration even when blank lines separate them
+fn is_commented(lines: &[String], index: usize) -> bool {
+    lines[..index]
+        .iter()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| is_comment(line))
+}
+
+impl FileSkeletons {
+    fn read(file: &str) -> Result<FileSkeletons, std::io::Error> {
+        let content = fs::read_to_string(file)?;
+        Ok(FileSkeletons {
+            file: String::from(file),
+            lines: content.split_inclusive('\n').map(String::from).collect(),
+            line_ending: match content.contains("\r\n") {
+                true => "\r\n",
+                false => "\n",
+            },
+            insertions: BTreeMap::new(),
+        })
+    }
+
+    /// Add a comment block above the declaration found at `line` (1-based)
+    fn insert(&mut self, line: i32, content: &[String]) {
+        if line < 1 || line as usize > self.lines.len() {
+            return;
+        }
+        let mut index = line as usize - 1;
+        while index > 0 && self.lines[index - 1].trim_start().starts_with("template") {
+            index -= 1;
+        }
+        if self.insertions.contains_key(&index) || is_commented(&self.lines, index) {
+            return;
+        }
+
+        let declaration = &self.lines[index];
+        let indent = &declaration[..declaration.len() - declaration.trim_start().len()];
+        let mut block = vec![format!("{}/**{}", indent, self.line_ending)];
+        for text in content {
+            block.push(format!("{} * {}{}", indent, text, self.line_ending));
+        }
+        block.push(format!("{} */{}", indent, self.line_ending));
+        self.insertions.insert(index, block);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.insertions.is_empty()
+    }
+
+    pub fn write_in_place(&self) -> Result<(), std::io::Error> {
+        let mut file = BufWriter::new(File::create(&self.file)?);
+        for (index, line) in self.lines.iter().enumerate() {
+            if let Some(block) = self.insertions.get(&index) {
+                for inserted in block {
+                    file.write_all(inserted.as_bytes())?;
+                }
+            }
+            file.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn write_line(diff: &mut dyn Write, prefix: char, line: &str) -> Result<(), std::io::Error> {
+        write!(diff, "{}{}", prefix, line)?;
+        if !line.ends_with('\n') {
+            write!(diff, "\n\\ No newline at end of file\n")?;
+        }
+        Ok(())
+    }
+
+    /// Write the insertions as a unified diff relative to the current folder
+    pub fn write_diff(&self, diff: &mut dyn Write) -> Result<(), std::io::Error> {
+        let path = Path::new(&self.file);
+        let relative = match std::env::current_dir() {
+            Ok(current) => path.strip_prefix(current).unwrap_or(path),
+            Err(_) => path,
+        };
+        let name = relative.to_string_lossy().replace('\\', "/");
+        let name = name.trim_start_matches('/');
+        writeln!(diff, "--- a/{}", name)?;
+        writeln!(diff, "+++ b/{}", name)?;
+
+        // Insertions closer than twice the context share a hunk
+        let indexes: Vec<&usize> = self.insertions.keys().collect();
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for &index in indexes {
+            match groups.last_mut() {
+                Some(group) if index - group.last().unwrap() <= 2 * CONTEXT => group.push(index),
+                _ => groups.push(vec![index]),
+            }
+        }
+
+        let mut offset = 0;
+        for group in groups {
+            let start = group[0].saturating_sub(CONTEXT);
+            let end = (group.last().unwrap() + CONTEXT).min(self.lines.len());
+            let added: usize = group.iter().map(|i| self.insertions[i].len()).sum();
+            writeln!(
+                diff,
+                "@@ -{},{} +{},{} @@",
+                start + 1,
+                end - start,
+                start + offset + 1,
+                end - start + added
+            )?;
+            for index in start..end {
+                if let Some(block) = self.insertions.get(&index) {
+                    for inserted in block {
+                        FileSkeletons::write_line(diff, '+', inserted)?;
+                    }
+                }
+                FileSkeletons::write_line(diff, ' ', &self.lines[index])?;
+            }
+            offset += added;
+        }
+        Ok(())
+    }
+}
+
+/// Comment blocks for the undocumented entities, grouped by file
+pub fn skeletons(project: &Project) -> Vec<FileSkeletons> {
+    let mut files: BTreeMap<&str, Option<FileSkeletons>> = BTreeMap::new();
+    for (location, content) in undocumented(project) {
+        let skeletons = files
+            .entry(location.file.as_str())
+            .or_insert_with(|| FileSkeletons::read(&location.file).ok());
+        if let Some(skeletons) = skeletons {
+            skeletons.insert(location.line, &content);
+        }
+    }
+    files
+        .into_values()
+        .flatten()
+        .filter(|skeletons| !skeletons.is_empty())
+        .collect()
+}
+
+/// Write the skeletons in the sources if `target` is `in-place`, as a patch
+/// file named `target` otherwise
+pub fn fix(project: &Project, target: &str) -> Result<usize, std::io::Error> {
+    let skeletons = skeletons(project);
+    if target == "in-place" {
+        for file in &skeletons {
+            file.write_in_place()?;
+        }
+    } else {
+        let mut diff = BufWriter::new(File::create(target)?);
+        for file in &skeletons {
+            file.write_diff(&mut diff)?;
+        }
+    }
+    Ok(skeletons.iter().map(|file| file.insertions.len()).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn direction(ctype: &str) -> &'static str {
+        parameter_direction(&Some(String::from(ctype)))
+    }
+
+    fn skeletons_of(source: &str, line: i32) -> FileSkeletons {
+        let mut skeletons = FileSkeletons {
+            file: String::from("a.h"),
+            lines: source.split_inclusive('\n').map(String::from).collect(),
+            line_ending: "\n",
+            insertions: BTreeMap::new(),
+        };
+        skeletons.insert(line, &[String::from("@brief TODO")]);
+        skeletons
+    }
+
+    #[test]
+    fn direction_of_values_and_const_pointees() {
+        assert_eq!(direction("int"), "in");
+        assert_eq!(direction("const Foo &"), "in");
+        assert_eq!(direction("Foo const*"), "in");
+        assert_eq!(direction("const char *const"), "in");
+        assert_eq!(direction("Foo &&"), "in");
+    }
+
+    #[test]
+    fn direction_of_non_const_pointees() {
+        assert_eq!(direction("Foo &"), "out");
+        assert_eq!(direction("Foo *const"), "out");
+        assert_eq!(direction("const Foo **"), "out");
+        assert_eq!(direction("std::vector< const int > &"), "out");
+    }
+
+    #[test]
+    fn comment_above_blank_lines_is_kept() {
+        let source = "/**\n * Size\n */\n\nint size();\n";
+        assert!(skeletons_of(source, 5).is_empty());
+    }
+
+    #[test]
+    fn skeleton_inserted_above_template() {
+        let source = "int a;\ntemplate <typename T>\nvoid f(T t);\n";
+        let skeletons = skeletons_of(source, 3);
+        assert_eq!(skeletons.insertions.keys().collect::<Vec<_>>(), [&1]);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::common::*;
+
+use std::fs::{self, File};
+use std::io::{BufReader, Error};
+use std::path::{Path, PathBuf};
+use xml::reader::{EventReader, XmlEvent};
+
+pub fn is_project_file(input: &str) -> bool {
+    let lower = input.to_lowercase();
+    lower.ends_with(".vcxproj") || lower.ends_with(".sln")
+}
+
+/// Path of an MSBuild or solution item, relative to `folder`
+fn resolve(folder: &Path, item: &str) -> PathBuf {
+    let path = folder.join(item.replace('\\', "/"));
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+fn folder_of(filename: &str) -> PathBuf {
+    match Path::new(filename).parent() {
+        Some(folder) if !folder.as_os_str().is_empty() => folder.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Headers and sources of the ClInclude and ClCompile items of a .vcxproj
+pub fn read_vcxproj(
+    filename: &str,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<String>, Error> {
+    let folder = folder_of(filename);
+    let file = BufReader::new(File::open(filename)?);
+    let parser = EventReader::new(file);
+
+    let mut files = Vec::new();
+    for event in parser {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) if name.local_name == "ClInclude" || name.local_name == "ClCompile" => {
+                let item = match read_xml_attribute(&attributes, "Include") {
+                    Some(item) => item,
+                    None => continue,
+                };
+                if item.contains("$(") || item.contains('*') {
+                    warnings.push(ParseWarning::new(format!(
+                        "{} of {} uses MSBuild properties or wildcards, skipped",
+                        item, filename
+                    )));
+                    continue;
+                }
+                let path = resolve(&folder, &item);
+                if !path.is_file() {
+                    warnings.push(ParseWarning::new(format!(
+                        "{} listed in {} does not exist, skipped",
+                        item, filename
+                    )));
+                    continue;
+                }
+                files.push(path.to_string_lossy().into_owned());
+            }
+            Ok(_) => {}
+            Err(e) => return Err(Error::other(format!("{}: {}", filename, e))),
+        }
+    }
+    Ok(files)
+}
+
+/// .vcxproj files of a solution
+pub fn read_sln(filename: &str) -> Result<Vec<String>, Error> {
+    let folder = folder_of(filename);
+    let text = fs::read_to_string(filename)?;
+    let mut projects = Vec::new();
+    for line in text.lines() {
+        // Project("{type}") = "name", "path", "{guid}"
+        let values = match line.trim_start().strip_prefix("Project(") {
+            Some(_) => match line.split_once('=') {
+                Some((_, values)) => values,
+                None => continue,
+            },
+            None => continue,
+        };
+        if let Some(path) = values
+            .split(',')
+            .nth(1)
+            .map(|path| path.trim().trim_matches('"'))
+            .filter(|path| path.to_lowercase().ends_with(".vcxproj"))
+        {
+            projects.push(resolve(&folder, path).to_string_lossy().into_owned());
+        }
+    }
+    Ok(projects)
+}
+
+/// Replace the .vcxproj and .sln inputs by the files they list, other inputs
+/// are kept as they are
+pub fn expand_project_files(
+    inputs: &[String],
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<String>, Error> {
+    let mut expanded: Vec<String> = Vec::new();
+    for input in inputs {
+        if !is_project_file(input) {
+            expanded.push(input.clone());
+            continue;
+        }
+        let projects = if input.to_lowercase().ends_with(".sln") {
+            read_sln(input)?
+        } else {
+            vec![input.clone()]
+        };
+        let mut found = false;
+        for project in projects {
+            for file in read_vcxproj(&project, warnings)? {
+                found = true;
+                if !expanded.contains(&file) {
+                    expanded.push(file);
+                }
+            }
+        }
+        // Doxygen would analyse nothing rather than fail
+        if !found {
+            return Err(Error::other(format!("no source file listed in {}", input)));
+        }
+    }
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_without_sources_is_an_error() {
+        let filename = std::env::temp_dir().join("cpp-analyzer-empty.vcxproj");
+        fs::write(
+            &filename,
+            r#"<Project><ItemGroup><ClCompile Include="$(Generated)\a.cpp" /></ItemGroup></Project>"#,
+        )
+        .unwrap();
+        let filename = filename.to_string_lossy().into_owned();
+        let mut warnings = Vec::new();
+        let result = expand_project_files(std::slice::from_ref(&filename), &mut warnings);
+        fs::remove_file(&filename).unwrap();
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("no source file listed in {}", filename)
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+}